@@ -0,0 +1,32 @@
+#![no_main]
+
+use day1::{
+    parse_5_digit_number_i32, parse_5_digit_number_i32x4_simd, parse_5_digit_number_i32x8_simd,
+    parse_5_digit_number_i64, parse_number_swar, try_parse_5_digit_number_i32,
+};
+use libfuzzer_sys::fuzz_target;
+
+// The fixed offset parsers only accept ASCII digits, so map every byte onto one
+// and check they all agree with each other and the standard library
+fuzz_target!(|data: &[u8]| {
+    let digits = data.iter().map(|b| b'0' + b % 10).collect::<Vec<_>>();
+
+    for number in digits.chunks_exact(5) {
+        let expected = std::str::from_utf8(number).unwrap().parse::<i32>().unwrap();
+
+        assert_eq!(parse_5_digit_number_i64(number), expected as i64);
+        assert_eq!(parse_5_digit_number_i32(number), expected);
+        assert_eq!(parse_5_digit_number_i32x4_simd(number), expected);
+        assert_eq!(parse_5_digit_number_i32x8_simd(number), expected);
+    }
+
+    // Up to 19 digits always fit in a u64
+    for number in digits.chunks(19) {
+        let expected = std::str::from_utf8(number).unwrap().parse::<u64>().unwrap();
+        assert_eq!(parse_number_swar(number), expected);
+    }
+
+    // The checked parser must never panic, whatever it's given
+    let checked = try_parse_5_digit_number_i32(data);
+    assert_eq!(checked.is_some(), data.len() == 5 && data.iter().all(u8::is_ascii_digit));
+});
@@ -0,0 +1,25 @@
+#![no_main]
+
+use day1::try_parse_columns;
+use libfuzzer_sys::fuzz_target;
+
+// Arbitrary bytes must either be rejected or give the same numbers as parsing
+// each line with the standard library
+fuzz_target!(|data: &[u8]| {
+    let Ok((left, right)) = try_parse_columns(data) else {
+        return;
+    };
+
+    let text = std::str::from_utf8(data).expect("accepted input is ASCII");
+    let lines = text.lines().collect::<Vec<_>>();
+
+    assert_eq!(left.len(), lines.len());
+    assert_eq!(right.len(), lines.len());
+
+    for ((line, l), r) in lines.iter().zip(left).zip(right) {
+        let mut numbers = line.split_whitespace().map(|n| n.parse::<i32>().unwrap());
+        assert_eq!(numbers.next(), Some(l));
+        assert_eq!(numbers.next(), Some(r));
+        assert_eq!(numbers.next(), None);
+    }
+});
@@ -0,0 +1,19 @@
+//! This crate's known answers, from the `answers.txt` next to `Cargo.toml`.
+//! See [`aoc_shared::answers`] for the format.
+
+pub use aoc_shared::answers::*;
+
+/// The answers in `answers.txt`
+pub fn known() -> Answers {
+    Answers::parse(include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/answers.txt"
+    )))
+    .expect("answers.txt is valid")
+}
+
+/// The known answer to `part` of `input`, for asserting against
+#[cfg(test)]
+pub(crate) fn expected<T: std::str::FromStr>(input: &[u8], part: &str) -> T {
+    known().require(input, part)
+}
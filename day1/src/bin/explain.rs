@@ -0,0 +1,61 @@
+use std::{env, fs, process};
+
+use day1::explain::Explanation;
+
+const USAGE: &str = "usage: explain <input> [csv output] [histogram bucket width]";
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    if args.is_empty() {
+        eprintln!("{USAGE}");
+        process::exit(1);
+    }
+
+    let input = fs::read(&args[0]).unwrap_or_else(|e| {
+        eprintln!("failed to read {}: {e}", args[0]);
+        process::exit(1);
+    });
+
+    let explanation = Explanation::from_bytes(&input).unwrap_or_else(|e| {
+        eprintln!("invalid input: {e}");
+        process::exit(1);
+    });
+
+    let bucket_width = match args.get(2) {
+        Some(arg) => arg
+            .parse()
+            .ok()
+            .filter(|width| *width > 0)
+            .unwrap_or_else(|| {
+                eprintln!("invalid histogram bucket width: {arg}\n{USAGE}");
+                process::exit(1);
+            }),
+        None => 1000,
+    };
+
+    println!("pairs:  {}", explanation.pairs.len());
+    println!("total:  {}", explanation.total());
+    if let Some(max) = explanation.max() {
+        println!(
+            "max:    {} (rank {}, {} vs {})",
+            max.distance, max.rank, max.left, max.right
+        );
+    }
+    if let Some(median) = explanation.median() {
+        println!("median: {median}");
+    }
+
+    println!();
+    for (start, count) in explanation.histogram(bucket_width) {
+        println!("{start:>6}..{:<6} {count}", start + bucket_width);
+    }
+
+    if let Some(output) = args.get(1) {
+        let written = fs::File::create(output).and_then(|file| explanation.write_csv(file));
+        if let Err(e) = written {
+            eprintln!("failed to write {output}: {e}");
+            process::exit(1);
+        }
+    }
+}
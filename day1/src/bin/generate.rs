@@ -0,0 +1,65 @@
+use std::{env, process, str::FromStr};
+
+use day1::generate::{generate, ListOptions, Separator};
+
+const USAGE: &str =
+    "usage: generate <lines> <digits> <seed> <output> [spaces:<n>|tab|mixed] [duplicate ratio]";
+
+fn parse_or_exit<T: FromStr>(arg: &str, name: &str) -> T {
+    arg.parse().unwrap_or_else(|_| {
+        eprintln!("invalid {name}: {arg}\n{USAGE}");
+        process::exit(1);
+    })
+}
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    if args.len() < 4 {
+        eprintln!("{USAGE}");
+        process::exit(1);
+    }
+
+    let lines: usize = parse_or_exit(&args[0], "lines");
+    let digits: u32 = parse_or_exit(&args[1], "digits");
+    let seed: u64 = parse_or_exit(&args[2], "seed");
+    let output = &args[3];
+
+    if !(1..=18).contains(&digits) {
+        eprintln!("invalid digits: {digits}, must be between 1 and 18\n{USAGE}");
+        process::exit(1);
+    }
+
+    let separator = match args.get(4).map(String::as_str) {
+        None => Separator::Spaces(3),
+        Some("tab") => Separator::Tab,
+        Some("mixed") => Separator::Mixed,
+        Some(other) => match other.strip_prefix("spaces:") {
+            Some(n) => Separator::Spaces(parse_or_exit(n, "spaces")),
+            None => {
+                eprintln!("invalid separator: {other}\n{USAGE}");
+                process::exit(1);
+            }
+        },
+    };
+
+    let mut options = ListOptions {
+        digits,
+        separator,
+        ..ListOptions::new(lines)
+    };
+    if let Some(ratio) = args.get(5) {
+        options.duplicate_ratio = parse_or_exit(ratio, "duplicate ratio");
+        if !(0.0..=1.0).contains(&options.duplicate_ratio) {
+            eprintln!("invalid duplicate ratio: {ratio}, must be between 0 and 1\n{USAGE}");
+            process::exit(1);
+        }
+    }
+
+    let list = generate(seed, &options);
+
+    if let Err(e) = list.write_to(output) {
+        eprintln!("failed to write {output}: {e}");
+        process::exit(1);
+    }
+}
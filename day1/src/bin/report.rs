@@ -0,0 +1,77 @@
+use std::{env, fs, io, process};
+
+use day1::{
+    answers::{self, hash, Check},
+    report, try_parse_columns,
+};
+
+const USAGE: &str = "usage: report <input> [runs] [baseline variant] [markdown|csv]";
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    if args.is_empty() {
+        eprintln!("{USAGE}");
+        process::exit(1);
+    }
+
+    let input = fs::read(&args[0]).unwrap_or_else(|e| {
+        eprintln!("failed to read {}: {e}", args[0]);
+        process::exit(1);
+    });
+
+    // The variants don't check anything, so make sure they'll be timing real work
+    if let Err(e) = try_parse_columns(&input) {
+        eprintln!("invalid input: {e}");
+        process::exit(1);
+    }
+
+    let runs = match args.get(1) {
+        Some(arg) => match arg.parse() {
+            Ok(runs) if runs > 0 => runs,
+            _ => {
+                eprintln!("invalid runs: {arg}\n{USAGE}");
+                process::exit(1);
+            }
+        },
+        None => 100,
+    };
+
+    let baseline = args.get(2).map(String::as_str).unwrap_or("vec_i32");
+    let report = report::run(&input, runs, baseline).unwrap_or_else(|| {
+        eprintln!("no variant called {baseline}");
+        process::exit(1);
+    });
+
+    let written = match args.get(3).map(String::as_str) {
+        None | Some("markdown") => report.write_markdown(io::stdout()),
+        Some("csv") => report.write_csv(io::stdout()),
+        Some(other) => {
+            eprintln!("invalid format: {other}\n{USAGE}");
+            process::exit(1);
+        }
+    };
+    if let Err(e) = written {
+        eprintln!("failed to write the report: {e}");
+        process::exit(1);
+    }
+
+    let answers = answers::known();
+    if answers.get(&input, "part_1").is_none() {
+        eprintln!(
+            "no known part_1 answer for input {:016x}, add it to answers.txt to check it",
+            hash(&input)
+        );
+    }
+
+    let mut wrong = false;
+    for (row, answer) in report.rows.iter().zip(&report.answers) {
+        if let Check::Wrong { expected } = answers.check(&input, "part_1", answer) {
+            eprintln!("{} answered {answer}, expected {expected}", row.name);
+            wrong = true;
+        }
+    }
+    if wrong {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,232 @@
+//! Day 1 for any number of whitespace separated columns. Distances are taken
+//! between chosen pairs of columns, or from every column to a reference one.
+
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ColumnsError {
+    NotANumber {
+        line: usize,
+        column: usize,
+    },
+    NoSuchColumn {
+        column: usize,
+    },
+    /// A line with a different number of fields to the first line
+    WrongFieldCount {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for ColumnsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnsError::NotANumber { line, column } => {
+                write!(f, "line {line} column {column} is not a number")
+            }
+            ColumnsError::NoSuchColumn { column } => write!(f, "there is no column {column}"),
+            ColumnsError::WrongFieldCount {
+                line,
+                expected,
+                found,
+            } => write!(f, "line {line} has {found} fields, expected {expected}"),
+        }
+    }
+}
+
+impl std::error::Error for ColumnsError {}
+
+/// Indices of two columns to compare
+pub type ColumnPair = (usize, usize);
+
+/// Every column of the input, each sorted ascending
+pub struct Columns {
+    columns: Vec<Vec<i64>>,
+}
+
+impl Columns {
+    /// Split every line on whitespace, the nth field going into column n. Blank
+    /// lines are skipped, every other line needs as many fields as the first.
+    pub fn parse(input: &[u8]) -> Result<Self, ColumnsError> {
+        let mut columns: Vec<Vec<i64>> = Vec::new();
+
+        for (line, line_bytes) in input.split(|c| *c == b'\n').enumerate() {
+            let fields = line_bytes
+                .split(u8::is_ascii_whitespace)
+                .filter(|field| !field.is_empty())
+                .collect::<Vec<_>>();
+
+            if fields.is_empty() {
+                continue;
+            }
+            if columns.is_empty() {
+                columns.resize_with(fields.len(), Vec::new);
+            }
+            if fields.len() != columns.len() {
+                return Err(ColumnsError::WrongFieldCount {
+                    line,
+                    expected: columns.len(),
+                    found: fields.len(),
+                });
+            }
+
+            for (column, field) in fields.into_iter().enumerate() {
+                let value = std::str::from_utf8(field)
+                    .ok()
+                    .and_then(|field| field.parse().ok())
+                    .ok_or(ColumnsError::NotANumber { line, column })?;
+
+                columns[column].push(value);
+            }
+        }
+
+        for column in columns.iter_mut() {
+            column.sort_unstable();
+        }
+
+        Ok(Self { columns })
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Column `column`, sorted
+    pub fn column(&self, column: usize) -> Result<&[i64], ColumnsError> {
+        self.columns
+            .get(column)
+            .map(Vec::as_slice)
+            .ok_or(ColumnsError::NoSuchColumn { column })
+    }
+
+    /// Part 1 between columns `a` and `b`. Any two `i64`s can be nearly
+    /// `2^64` apart, so the total is a `u128`.
+    pub fn distance(&self, a: usize, b: usize) -> Result<u128, ColumnsError> {
+        let (a_values, b_values) = (self.column(a)?, self.column(b)?);
+
+        Ok(a_values
+            .iter()
+            .zip(b_values.iter())
+            .map(|(a, b)| a.abs_diff(*b) as u128)
+            .sum())
+    }
+
+    /// `distance` for each pair, in order
+    pub fn pairwise(&self, pairs: &[ColumnPair]) -> Result<Vec<u128>, ColumnsError> {
+        pairs.iter().map(|&(a, b)| self.distance(a, b)).collect()
+    }
+
+    /// `distance` from `reference` to every column, including itself (always 0)
+    pub fn against(&self, reference: usize) -> Result<Vec<u128>, ColumnsError> {
+        (0..self.len())
+            .map(|column| self.distance(reference, column))
+            .collect()
+    }
+
+    /// `distance` between every pair of columns `(a, b)` with `a < b`
+    pub fn all_pairs(&self) -> Result<Vec<(ColumnPair, u128)>, ColumnsError> {
+        (0..self.len())
+            .flat_map(|a| (a + 1..self.len()).map(move |b| (a, b)))
+            .map(|(a, b)| Ok(((a, b), self.distance(a, b)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        answers::expected,
+        generate::{generate, ListOptions, Separator},
+        INPUT,
+    };
+
+    #[test]
+    fn test_two_columns_match_day1() {
+        let columns = Columns::parse(INPUT).unwrap();
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns.distance(0, 1), Ok(expected(INPUT, "part_1")));
+        assert_eq!(columns.distance(1, 0), Ok(expected(INPUT, "part_1")));
+
+        // Any whitespace between the columns
+        let list = generate(
+            4,
+            &ListOptions {
+                separator: Separator::Mixed,
+                digits: 9,
+                ..ListOptions::new(300)
+            },
+        );
+        let columns = Columns::parse(&list.bytes).unwrap();
+        assert_eq!(columns.column(0).unwrap().len(), 300);
+        assert_eq!(columns.column(1).unwrap().len(), 300);
+    }
+
+    #[test]
+    fn test_many_columns() {
+        let input = b"3 4 1\n4 3 9\n2 5 2\n1 3 8\n3 9 3\n3 3 4\n";
+        let columns = Columns::parse(input).unwrap();
+
+        // The first two columns are the puzzle example
+        assert_eq!(columns.distance(0, 1), Ok(11));
+        assert_eq!(columns.pairwise(&[(0, 1), (0, 2)]), Ok(vec![11, 11]));
+        assert_eq!(columns.against(1), Ok(vec![11, 0, 6]));
+        assert_eq!(
+            columns.all_pairs(),
+            Ok(vec![((0, 1), 11), ((0, 2), 11), ((1, 2), 6)])
+        );
+    }
+
+    #[test]
+    fn test_extreme_values() {
+        let input = format!("{} {}\n{} {}\n", i64::MIN, i64::MAX, i64::MAX, i64::MIN);
+        let columns = Columns::parse(input.as_bytes()).unwrap();
+
+        // Sorted, both lines pair MIN with MIN and MAX with MAX
+        assert_eq!(columns.distance(0, 1), Ok(0));
+
+        let input = format!("{} {}\n{} {}\n", i64::MIN, i64::MAX, i64::MIN, i64::MAX);
+        let columns = Columns::parse(input.as_bytes()).unwrap();
+        assert_eq!(columns.distance(0, 1), Ok(2 * u64::MAX as u128));
+        assert_eq!(columns.against(0), Ok(vec![0, 2 * u64::MAX as u128]));
+    }
+
+    #[test]
+    fn test_errors() {
+        // A missing field would shift the rest of the line into the wrong columns
+        assert_eq!(
+            Columns::parse(b"1 2 3\n4 5\n6 7 8\n").err(),
+            Some(ColumnsError::WrongFieldCount {
+                line: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            Columns::parse(b"1 2\n\n3 4 5\n").err(),
+            Some(ColumnsError::WrongFieldCount {
+                line: 2,
+                expected: 2,
+                found: 3
+            })
+        );
+
+        let columns = Columns::parse(b"1 2 3\n\n6 7 8\n").unwrap();
+        assert_eq!(columns.distance(0, 1), Ok(2));
+        assert_eq!(
+            columns.against(3),
+            Err(ColumnsError::NoSuchColumn { column: 3 })
+        );
+
+        assert_eq!(
+            Columns::parse(b"1 2\n3 x\n").err(),
+            Some(ColumnsError::NotANumber { line: 1, column: 1 })
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! The sorted pairing behind the part 1 total, for checking answers by hand.
+
+use std::io::{self, Write};
+
+use crate::{try_parse_columns, ParseError};
+
+/// The `rank`th smallest left value paired with the `rank`th smallest right value
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Pair {
+    pub rank: usize,
+    pub left: i32,
+    pub right: i32,
+    pub distance: i32,
+}
+
+pub struct Explanation {
+    /// In rank order
+    pub pairs: Vec<Pair>,
+}
+
+impl Explanation {
+    pub fn from_columns(mut left_column: Vec<i32>, mut right_column: Vec<i32>) -> Self {
+        left_column.sort_unstable();
+        right_column.sort_unstable();
+
+        let pairs = left_column
+            .into_iter()
+            .zip(right_column)
+            .enumerate()
+            .map(|(rank, (left, right))| Pair {
+                rank,
+                left,
+                right,
+                distance: (left - right).abs(),
+            })
+            .collect();
+
+        Self { pairs }
+    }
+
+    /// Explain an input in the real input's layout
+    pub fn from_bytes(input: &[u8]) -> Result<Self, ParseError> {
+        let (left_column, right_column) = try_parse_columns(input)?;
+        Ok(Self::from_columns(left_column, right_column))
+    }
+
+    /// The part 1 answer
+    pub fn total(&self) -> i64 {
+        self.pairs.iter().map(|pair| pair.distance as i64).sum()
+    }
+
+    /// The pair furthest apart, the lowest rank if there's a tie
+    pub fn max(&self) -> Option<Pair> {
+        self.pairs.iter().copied().reduce(|max, pair| {
+            if pair.distance > max.distance {
+                pair
+            } else {
+                max
+            }
+        })
+    }
+
+    /// Median distance, halfway between the middle two for an even count
+    pub fn median(&self) -> Option<f64> {
+        let mut distances = self
+            .pairs
+            .iter()
+            .map(|pair| pair.distance)
+            .collect::<Vec<_>>();
+        distances.sort_unstable();
+
+        let middle = distances.len() / 2;
+        match distances.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(distances[middle] as f64),
+            _ => Some((distances[middle - 1] as f64 + distances[middle] as f64) / 2.0),
+        }
+    }
+
+    /// How many distances fall in each `bucket_width` wide bucket, starting at
+    /// 0 and running up to the bucket holding the max. Each entry is the
+    /// bucket's lowest distance and its count. Panics if `bucket_width` is 0.
+    pub fn histogram(&self, bucket_width: u32) -> Vec<(u32, usize)> {
+        assert!(bucket_width > 0, "buckets must be at least 1 wide");
+
+        let Some(max) = self.max() else {
+            return Vec::new();
+        };
+
+        let mut counts = vec![0; max.distance as usize / bucket_width as usize + 1];
+        for pair in &self.pairs {
+            counts[pair.distance as usize / bucket_width as usize] += 1;
+        }
+
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(bucket, count)| (bucket as u32 * bucket_width, count))
+            .collect()
+    }
+
+    /// One `rank,left,right,distance` row per pair, with a header
+    pub fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "rank,left,right,distance")?;
+        for pair in &self.pairs {
+            writeln!(
+                writer,
+                "{},{},{},{}",
+                pair.rank, pair.left, pair.right, pair.distance
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{answers::expected, INPUT};
+
+    fn example() -> Explanation {
+        Explanation::from_columns(vec![3, 4, 2, 1, 3, 3], vec![4, 3, 5, 3, 9, 3])
+    }
+
+    #[test]
+    fn test_example() {
+        let explanation = example();
+
+        assert_eq!(
+            explanation
+                .pairs
+                .iter()
+                .map(|pair| (pair.left, pair.right, pair.distance))
+                .collect::<Vec<_>>(),
+            [
+                (1, 3, 2),
+                (2, 3, 1),
+                (3, 3, 0),
+                (3, 4, 1),
+                (3, 5, 2),
+                (4, 9, 5)
+            ]
+        );
+        assert_eq!(explanation.total(), 11);
+        assert_eq!(
+            explanation.max(),
+            Some(Pair {
+                rank: 5,
+                left: 4,
+                right: 9,
+                distance: 5
+            })
+        );
+        assert_eq!(explanation.median(), Some(1.5));
+        assert_eq!(explanation.histogram(2), [(0, 3), (2, 2), (4, 1)]);
+    }
+
+    #[test]
+    fn test_empty() {
+        let explanation = Explanation::from_columns(Vec::new(), Vec::new());
+        assert_eq!(explanation.total(), 0);
+        assert_eq!(explanation.max(), None);
+        assert_eq!(explanation.median(), None);
+        assert!(explanation.histogram(10).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "buckets must be at least 1 wide")]
+    fn test_histogram_zero_width() {
+        example().histogram(0);
+    }
+
+    #[test]
+    fn test_input() {
+        let explanation = Explanation::from_bytes(INPUT).unwrap();
+        assert_eq!(explanation.pairs.len(), 1000);
+        assert_eq!(explanation.total(), expected(INPUT, "part_1"));
+
+        let histogram = explanation.histogram(1000);
+        assert_eq!(
+            histogram.iter().map(|(_, count)| count).sum::<usize>(),
+            1000
+        );
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let mut csv = Vec::new();
+        example().write_csv(&mut csv).unwrap();
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "rank,left,right,distance\n\
+             0,1,3,2\n\
+             1,2,3,1\n\
+             2,3,3,0\n\
+             3,3,4,1\n\
+             4,3,5,2\n\
+             5,4,9,5\n"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::{fs, io, path::Path};
+
+/// Tiny seeded PRNG so generated lists are reproducible without pulling in `rand`.
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `low..=high`
+    pub fn range(&mut self, low: u64, high: u64) -> u64 {
+        let span = high - low + 1;
+        low + self.next_u64() % span
+    }
+
+    /// True with probability `p`
+    pub fn chance(&mut self, p: f64) -> bool {
+        // Top 53 bits give a uniform f64 in 0..1
+        ((self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64) < p
+    }
+}
+
+/// What goes between the two columns.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Separator {
+    /// A fixed number of spaces, the real input uses 3
+    Spaces(usize),
+    Tab,
+    /// 1 to 4 spaces or tabs, picked per line
+    Mixed,
+}
+
+pub struct ListOptions {
+    pub lines: usize,
+    /// Every number has exactly this many digits, no leading zeros. At most 18.
+    pub digits: u32,
+    pub separator: Separator,
+    /// Chance of a number repeating one that has already been generated, in either
+    /// column. Gives part 2 something to count.
+    pub duplicate_ratio: f64,
+}
+
+impl ListOptions {
+    /// Same shape as the real input: 5 digits, 3 spaces, a handful of duplicates
+    pub fn new(lines: usize) -> Self {
+        Self {
+            lines,
+            digits: 5,
+            separator: Separator::Spaces(3),
+            duplicate_ratio: 0.01,
+        }
+    }
+}
+
+pub struct GeneratedList {
+    pub lines: usize,
+    /// The list in puzzle input format, every line terminated by a newline
+    pub bytes: Vec<u8>,
+}
+
+impl GeneratedList {
+    pub fn write_to(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, &self.bytes)
+    }
+}
+
+/// Generate a two column day 1 input. The same seed and options always produce
+/// the same list.
+pub fn generate(seed: u64, options: &ListOptions) -> GeneratedList {
+    assert!(
+        (1..=18).contains(&options.digits),
+        "digits must be between 1 and 18"
+    );
+
+    let mut rng = SplitMix64::new(seed);
+
+    let low = if options.digits == 1 {
+        0
+    } else {
+        10_u64.pow(options.digits - 1)
+    };
+    let high = 10_u64.pow(options.digits) - 1;
+
+    let mut generated = Vec::with_capacity(options.lines * 2);
+    let mut next_number = |rng: &mut SplitMix64| {
+        let n = if !generated.is_empty() && rng.chance(options.duplicate_ratio) {
+            generated[rng.range(0, generated.len() as u64 - 1) as usize]
+        } else {
+            rng.range(low, high)
+        };
+        generated.push(n);
+        n
+    };
+
+    let line_length = options.digits as usize * 2 + 6;
+    let mut bytes = Vec::with_capacity(options.lines * line_length);
+
+    for _ in 0..options.lines {
+        let left = next_number(&mut rng);
+        let right = next_number(&mut rng);
+
+        bytes.extend_from_slice(left.to_string().as_bytes());
+        match options.separator {
+            Separator::Spaces(n) => bytes.extend(std::iter::repeat_n(b' ', n)),
+            Separator::Tab => bytes.push(b'\t'),
+            Separator::Mixed => {
+                for _ in 0..rng.range(1, 4) {
+                    bytes.push(if rng.chance(0.5) { b' ' } else { b'\t' });
+                }
+            }
+        }
+        bytes.extend_from_slice(right.to_string().as_bytes());
+        bytes.push(b'\n');
+    }
+
+    GeneratedList {
+        lines: options.lines,
+        bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_default_shape() {
+        let list = generate(1, &ListOptions::new(1000));
+
+        // Same fixed 14 byte records as the real input
+        assert_eq!(list.bytes.len(), 1000 * 14);
+        for line in list.bytes.chunks(14) {
+            assert!(line[0..5].iter().all(u8::is_ascii_digit));
+            assert_eq!(&line[5..8], b"   ");
+            assert!(line[8..13].iter().all(u8::is_ascii_digit));
+            assert_eq!(line[13], b'\n');
+        }
+    }
+
+    #[test]
+    fn test_generate_is_seeded() {
+        let options = ListOptions::new(100);
+        assert_eq!(generate(7, &options).bytes, generate(7, &options).bytes);
+        assert_ne!(generate(7, &options).bytes, generate(8, &options).bytes);
+    }
+
+    #[test]
+    fn test_generate_options() {
+        let list = generate(
+            3,
+            &ListOptions {
+                lines: 500,
+                digits: 9,
+                separator: Separator::Mixed,
+                duplicate_ratio: 0.5,
+            },
+        );
+
+        let numbers = list
+            .bytes
+            .split(|c| c.is_ascii_whitespace())
+            .filter(|n| !n.is_empty())
+            .collect::<Vec<_>>();
+
+        assert_eq!(numbers.len(), 1000);
+        assert!(numbers.iter().all(|n| n.len() == 9 && n[0] != b'0'));
+
+        let mut unique = numbers.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert!(unique.len() < 750, "expected plenty of duplicates");
+    }
+}
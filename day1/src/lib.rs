@@ -1,13 +1,33 @@
-#![feature(portable_simd, binary_heap_into_iter_sorted, test)]
-
-use std::{
-    collections::BinaryHeap,
-    simd::{i32x4, i32x8, num::SimdInt},
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+#![cfg_attr(all(test, feature = "bench"), feature(test))]
+
+pub mod answers;
+pub mod columns;
+pub mod explain;
+pub mod generate;
+pub mod online;
+pub mod parallel;
+pub mod pipeline;
+#[cfg(test)]
+mod reference;
+pub mod report;
+#[cfg(feature = "simd")]
+pub mod simd_sort;
+pub mod stream;
+
+pub use aoc_shared::dispatch;
+use pipeline::{
+    distance, BatchSimd, Radix, Reduce, Scalar, SimdI32x4, SimdI32x8, SimdReduce, SimdSort, Stable,
+    Swar, Unstable,
 };
+use std::collections::BinaryHeap;
+#[cfg(feature = "simd")]
+use std::simd::{i32x4, i32x8, num::SimdInt, num::SimdUint, simd_swizzle, u8x64, u8x8};
 
+#[cfg(test)]
 const INPUT: &[u8] = include_bytes!("../input.txt");
 
-fn parse_5_digit_number_i64(ascii_bytes: &[u8]) -> i64 {
+pub fn parse_5_digit_number_i64(ascii_bytes: &[u8]) -> i64 {
     (ascii_bytes[0] - b'0') as i64 * 10000
         + (ascii_bytes[1] - b'0') as i64 * 1000
         + (ascii_bytes[2] - b'0') as i64 * 100
@@ -15,7 +35,7 @@ fn parse_5_digit_number_i64(ascii_bytes: &[u8]) -> i64 {
         + (ascii_bytes[4] - b'0') as i64
 }
 
-fn parse_5_digit_number_i32(ascii_bytes: &[u8]) -> i32 {
+pub fn parse_5_digit_number_i32(ascii_bytes: &[u8]) -> i32 {
     (ascii_bytes[0] - b'0') as i32 * 10000
         + (ascii_bytes[1] - b'0') as i32 * 1000
         + (ascii_bytes[2] - b'0') as i32 * 100
@@ -23,8 +43,8 @@ fn parse_5_digit_number_i32(ascii_bytes: &[u8]) -> i32 {
         + (ascii_bytes[4] - b'0') as i32
 }
 
-fn parse_5_digit_number_i32x8_simd(ascii_bytes: &[u8]) -> i32 {
-    let data = i32x8::from_array([
+pub fn parse_5_digit_number_i32x8_simd(ascii_bytes: &[u8]) -> i32 {
+    let data = [
         0,
         0,
         0,
@@ -33,8 +53,8 @@ fn parse_5_digit_number_i32x8_simd(ascii_bytes: &[u8]) -> i32 {
         ascii_bytes[2] as i32,
         ascii_bytes[3] as i32,
         ascii_bytes[4] as i32,
-    ]);
-    let ascii_offset = i32x8::from_array([
+    ];
+    let ascii_offset = [
         0,
         0,
         0,
@@ -43,143 +63,417 @@ fn parse_5_digit_number_i32x8_simd(ascii_bytes: &[u8]) -> i32 {
         b'0' as i32,
         b'0' as i32,
         b'0' as i32,
-    ]);
-    let multipliers = i32x8::from_array([0, 0, 0, 10000, 1000, 100, 10, 1]);
-    let result = (data - ascii_offset) * multipliers;
-
-    result.reduce_sum()
+    ];
+    let multipliers = [0, 0, 0, 10000, 1000, 100, 10, 1];
+
+    #[cfg(feature = "simd")]
+    {
+        let result = (i32x8::from_array(data) - i32x8::from_array(ascii_offset))
+            * i32x8::from_array(multipliers);
+        result.reduce_sum()
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        (0..8)
+            .map(|i| (data[i] - ascii_offset[i]) * multipliers[i])
+            .sum()
+    }
 }
 
-fn parse_5_digit_number_i32x4_simd(ascii_bytes: &[u8]) -> i32 {
-    let data = i32x4::from_array([
+pub fn parse_5_digit_number_i32x4_simd(ascii_bytes: &[u8]) -> i32 {
+    let data = [
         ascii_bytes[0] as i32,
         ascii_bytes[1] as i32,
         ascii_bytes[2] as i32,
         ascii_bytes[3] as i32,
-    ]);
-    let ascii_offset = i32x4::from_array([b'0' as i32, b'0' as i32, b'0' as i32, b'0' as i32]);
-
-    let multipliers = i32x4::from_array([10000, 1000, 100, 10]);
-    let result = (data - ascii_offset) * multipliers;
-    let simd_sum = result.reduce_sum();
+    ];
+    let ascii_offset = [b'0' as i32, b'0' as i32, b'0' as i32, b'0' as i32];
+    let multipliers = [10000, 1000, 100, 10];
+
+    #[cfg(feature = "simd")]
+    let simd_sum = ((i32x4::from_array(data) - i32x4::from_array(ascii_offset))
+        * i32x4::from_array(multipliers))
+    .reduce_sum();
+    #[cfg(not(feature = "simd"))]
+    let simd_sum = (0..4)
+        .map(|i| (data[i] - ascii_offset[i]) * multipliers[i])
+        .sum::<i32>();
 
     simd_sum + ascii_bytes[4] as i32 - b'0' as i32
 }
 
-fn vec_i64(input: &[u8]) -> i64 {
-    let (mut left_column, mut right_column) = input
-        .chunks(14)
-        .map(|line_bytes| {
-            let left = parse_5_digit_number_i64(&line_bytes[0..5]);
-            let right = parse_5_digit_number_i64(&line_bytes[8..13]);
-            (left, right)
-        })
-        .collect::<(Vec<i64>, Vec<i64>)>();
-
-    left_column.sort();
-    right_column.sort();
+/// Parse up to 8 ASCII digits at once in a `u64`. Each step combines
+/// neighbouring bytes, then pairs, then quads with a multiply and a shift, so it
+/// needs no `portable_simd` and works on stable. Shorter numbers are shifted up
+/// so the missing leading digits read as zeros.
+fn parse_8_digits_swar(ascii_bytes: &[u8]) -> u32 {
+    debug_assert!(ascii_bytes.len() <= 8, "at most 8 digits fit in a u64");
 
-    left_column
+    // Little endian, so the first digit ends up in the lowest byte. Building it
+    // byte by byte is much quicker than a variable length copy into an array.
+    let chunk = ascii_bytes
         .iter()
-        .zip(right_column.iter())
-        .fold(0_i64, |acc, (left, right)| acc + (left - right).abs())
+        .rev()
+        .fold(0_u64, |chunk, byte| (chunk << 8) | *byte as u64);
+
+    // Subtracting from the padding only borrows upwards, so the shift throws
+    // that away along with the padding
+    let chunk = chunk.wrapping_sub(0x3030303030303030);
+    let chunk = chunk
+        .checked_shl(8 * (8 - ascii_bytes.len() as u32))
+        .unwrap_or(0);
+
+    let chunk = (chunk.wrapping_mul(10) + (chunk >> 8)) & 0x00ff00ff00ff00ff;
+    let chunk = (chunk.wrapping_mul(100) + (chunk >> 16)) & 0x0000ffff0000ffff;
+    let chunk = (chunk.wrapping_mul(10000) + (chunk >> 32)) & 0x00000000ffffffff;
+
+    chunk as u32
 }
 
-fn vec_i32(input: &[u8]) -> i32 {
-    let (mut left_column, mut right_column) = input
-        .chunks(14)
-        .map(|line_bytes| {
-            let left = parse_5_digit_number_i32(&line_bytes[0..5]);
-            let right = parse_5_digit_number_i32(&line_bytes[8..13]);
-            (left, right)
-        })
-        .collect::<(Vec<i32>, Vec<i32>)>();
+/// Parse any number of ASCII digits with `parse_8_digits_swar`, one pass per 8
+/// digits. The number has to fit in a `u64`.
+pub fn parse_number_swar(ascii_bytes: &[u8]) -> u64 {
+    let (head, tail) = ascii_bytes.split_at(ascii_bytes.len().saturating_sub(8));
+    let high = if head.is_empty() {
+        0
+    } else {
+        parse_number_swar(head)
+    };
+
+    high * 100_000_000 + parse_8_digits_swar(tail) as u64
+}
 
-    left_column.sort();
-    right_column.sort();
+/// Byte length of one `12345   67890\n` line
+pub const LINE_LENGTH: usize = 14;
+
+// 4 whole lines fit into a 64 byte block
+const LINES_PER_BLOCK: usize = 4;
+
+/// Where the `digit`th digit of all 8 numbers in a block of 4 lines lives.
+/// Lanes 0..4 are the left column, lanes 4..8 the right.
+const fn digit_indices(digit: usize) -> [usize; 8] {
+    let mut indices = [0; 8];
+    let mut lane = 0;
+    while lane < 8 {
+        let line = lane % LINES_PER_BLOCK;
+        let column = lane / LINES_PER_BLOCK;
+        indices[lane] = line * LINE_LENGTH + column * 8 + digit;
+        lane += 1;
+    }
+    indices
+}
 
-    left_column
-        .iter()
-        .zip(right_column.iter())
-        .fold(0_i32, |acc, (left, right)| acc + (left - right).abs())
+const DIGIT_0: [usize; 8] = digit_indices(0);
+const DIGIT_1: [usize; 8] = digit_indices(1);
+const DIGIT_2: [usize; 8] = digit_indices(2);
+const DIGIT_3: [usize; 8] = digit_indices(3);
+const DIGIT_4: [usize; 8] = digit_indices(4);
+
+/// Parse 4 lines out of a 64 byte block at once. Shuffles each digit place of
+/// all 8 numbers into its own vector and accumulates them with a multiply-add,
+/// so it comes out as `[left; 4]` then `[right; 4]`.
+#[cfg(feature = "simd")]
+#[inline(always)]
+fn parse_block_i32x8_simd(block: u8x64) -> i32x8 {
+    let digit = |d: u8x8| d.cast::<i32>() - i32x8::splat(b'0' as i32);
+    let ten = i32x8::splat(10);
+
+    let mut numbers = digit(simd_swizzle!(block, DIGIT_0));
+    numbers = numbers * ten + digit(simd_swizzle!(block, DIGIT_1));
+    numbers = numbers * ten + digit(simd_swizzle!(block, DIGIT_2));
+    numbers = numbers * ten + digit(simd_swizzle!(block, DIGIT_3));
+    numbers * ten + digit(simd_swizzle!(block, DIGIT_4))
 }
 
-fn vec_i32_simd_parse(input: &[u8]) -> i32 {
-    let (mut left_column, mut right_column) = input
-        .chunks(14)
-        .map(|line_bytes| {
-            let left = parse_5_digit_number_i32x8_simd(&line_bytes[0..5]);
-            let right = parse_5_digit_number_i32x8_simd(&line_bytes[8..13]);
-            (left, right)
-        })
-        .collect::<(Vec<i32>, Vec<i32>)>();
+/// The 4 lines at the start of `rest` as `[left; 4]` then `[right; 4]`, reading
+/// zeros past its end
+#[inline(always)]
+fn parse_block(rest: &[u8]) -> [i32; 8] {
+    #[cfg(feature = "simd")]
+    {
+        // Full loads while there are 64 bytes left, padded with zeros after that
+        let block = if rest.len() >= 64 {
+            u8x64::from_slice(rest)
+        } else {
+            u8x64::load_or_default(rest)
+        };
+        parse_block_i32x8_simd(block).to_array()
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        let mut block = [0; 64];
+        let len = rest.len().min(64);
+        block[..len].copy_from_slice(&rest[..len]);
+
+        let mut numbers = [0; 8];
+        for digit in [DIGIT_0, DIGIT_1, DIGIT_2, DIGIT_3, DIGIT_4] {
+            for (number, index) in numbers.iter_mut().zip(digit) {
+                *number = *number * 10 + block[index] as i32 - b'0' as i32;
+            }
+        }
+        numbers
+    }
+}
 
-    left_column.sort_unstable();
-    right_column.sort_unstable();
+dispatch::multiversion! {
+    /// Parse both columns 4 lines at a time. Like the other fast parsers it trusts
+    /// the input to be in the fixed 14 byte layout. The last line may be missing
+    /// its newline.
+    pub fn parse_columns_batch_simd(input: &[u8]) -> (Vec<i32>, Vec<i32>) {
+        let lines = input.len().div_ceil(LINE_LENGTH);
+        let mut left_column = Vec::with_capacity(lines);
+        let mut right_column = Vec::with_capacity(lines);
 
-    left_column
-        .iter()
-        .zip(right_column.iter())
-        .fold(0_i32, |acc, (left, right)| acc + (left - right).abs())
+        let mut line = 0;
+
+        while line < lines {
+            let numbers = parse_block(&input[line * LINE_LENGTH..]);
+            let count = LINES_PER_BLOCK.min(lines - line);
+
+            left_column.extend_from_slice(&numbers[0..count]);
+            right_column.extend_from_slice(&numbers[4..4 + count]);
+
+            line += LINES_PER_BLOCK;
+        }
+
+        (left_column, right_column)
+    }
 }
 
-fn vec_i32_unstable(input: &[u8]) -> i32 {
-    let (mut left_column, mut right_column) = input
-        .chunks(14)
-        .map(|line_bytes| {
-            let left = parse_5_digit_number_i32(&line_bytes[0..5]);
-            let right = parse_5_digit_number_i32(&line_bytes[8..13]);
-            (left, right)
-        })
-        .collect::<(Vec<i32>, Vec<i32>)>();
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input doesn't end on a line boundary
+    TruncatedLine {
+        line: usize,
+    },
+    NotADigit {
+        offset: usize,
+        byte: u8,
+    },
+    BadSeparator {
+        offset: usize,
+    },
+    MissingNewline {
+        offset: usize,
+    },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::TruncatedLine { line } => write!(f, "line {line} is truncated"),
+            ParseError::NotADigit { offset, byte } => {
+                write!(f, "expected a digit at offset {offset}, found {byte:#04x}")
+            }
+            ParseError::BadSeparator { offset } => {
+                write!(f, "expected 3 spaces at offset {offset}")
+            }
+            ParseError::MissingNewline { offset } => {
+                write!(f, "expected a newline at offset {offset}")
+            }
+        }
+    }
+}
 
-    left_column.sort_unstable();
-    right_column.sort_unstable();
+impl std::error::Error for ParseError {}
 
-    left_column
-        .iter()
-        .zip(right_column.iter())
-        .fold(0_i32, |acc, (left, right)| acc + (left - right).abs())
+/// Checked version of the 5 digit parsers, which trust their input and will
+/// panic or give garbage on anything that isn't 5 ASCII digits
+pub fn try_parse_5_digit_number_i32(ascii_bytes: &[u8]) -> Option<i32> {
+    match ascii_bytes {
+        [_, _, _, _, _] if ascii_bytes.iter().all(u8::is_ascii_digit) => {
+            Some(parse_5_digit_number_i32(ascii_bytes))
+        }
+        _ => None,
+    }
 }
 
-fn vec_i32_unstable_simd(input: &[u8]) -> i32 {
-    let (mut left_column, mut right_column) = input
-        .chunks(14)
-        .map(|line_bytes| {
-            let left = parse_5_digit_number_i32x4_simd(&line_bytes[0..5]);
-            let right = parse_5_digit_number_i32x4_simd(&line_bytes[8..13]);
-            (left, right)
-        })
-        .collect::<(Vec<i32>, Vec<i32>)>();
+/// Parse both columns, checking every line has the fixed layout the fast
+/// parsers rely on
+pub fn try_parse_columns(input: &[u8]) -> Result<(Vec<i32>, Vec<i32>), ParseError> {
+    let mut left_column = Vec::with_capacity(input.len() / LINE_LENGTH);
+    let mut right_column = Vec::with_capacity(input.len() / LINE_LENGTH);
 
-    left_column.sort_unstable();
-    right_column.sort_unstable();
+    try_parse_lines_into(input, 0, &mut left_column, &mut right_column)?;
 
-    const LANES: usize = 4;
+    Ok((left_column, right_column))
+}
 
-    let mut sum = 0;
-    let chunks = left_column.len() / LANES;
+/// `try_parse_columns` for a piece of the input starting at line `first_line`,
+/// so errors still point at the right place in the whole input
+pub(crate) fn try_parse_lines_into(
+    input: &[u8],
+    first_line: usize,
+    left_column: &mut Vec<i32>,
+    right_column: &mut Vec<i32>,
+) -> Result<(), ParseError> {
+    for (i, line_bytes) in input.chunks(LINE_LENGTH).enumerate() {
+        let line = first_line + i;
+        let offset = line * LINE_LENGTH;
+
+        // The last line doesn't have to end in a newline, the real input doesn't
+        let is_last = (i * LINE_LENGTH) + line_bytes.len() == input.len();
+        if line_bytes.len() != LINE_LENGTH && !(is_last && line_bytes.len() == LINE_LENGTH - 1) {
+            return Err(ParseError::TruncatedLine { line });
+        }
+
+        let number = |range: std::ops::Range<usize>| {
+            try_parse_5_digit_number_i32(&line_bytes[range.clone()]).ok_or_else(|| {
+                let i = range.start
+                    + line_bytes[range]
+                        .iter()
+                        .position(|c| !c.is_ascii_digit())
+                        .unwrap_or(0);
+                ParseError::NotADigit {
+                    offset: offset + i,
+                    byte: line_bytes[i],
+                }
+            })
+        };
+
+        let left = number(0..5)?;
+
+        if &line_bytes[5..8] != b"   " {
+            return Err(ParseError::BadSeparator { offset: offset + 5 });
+        }
+
+        let right = number(8..13)?;
+
+        if line_bytes.get(13).is_some_and(|c| *c != b'\n') {
+            return Err(ParseError::MissingNewline {
+                offset: offset + 13,
+            });
+        }
+
+        left_column.push(left);
+        right_column.push(right);
+    }
+
+    Ok(())
+}
+
+// The vec_* variants only differ in one stage each, see `pipeline`
+fn vec_i64<R: Reduce<i64>>(input: &[u8]) -> R::Output {
+    distance::<i64, Scalar, Stable, R>(input)
+}
 
-    for i in 0..chunks {
-        let left = i32x4::from_slice(&left_column[i * LANES..i * LANES + LANES]);
-        let right = i32x4::from_slice(&right_column[i * LANES..i * LANES + LANES]);
-        let diff = left - right;
-        let abs_diff = diff.abs();
-        sum += abs_diff.reduce_sum();
+fn vec_i32<R: Reduce<i32>>(input: &[u8]) -> R::Output {
+    distance::<i32, Scalar, Stable, R>(input)
+}
+
+fn vec_i32_simd_parse<R: Reduce<i32>>(input: &[u8]) -> R::Output {
+    distance::<i32, SimdI32x8, Unstable, R>(input)
+}
+
+fn vec_i32_unstable<R: Reduce<i32>>(input: &[u8]) -> R::Output {
+    distance::<i32, Scalar, Unstable, R>(input)
+}
+
+fn vec_i32_unstable_simd<R: Reduce<i32>>(input: &[u8]) -> R::Output {
+    distance::<i32, SimdI32x4, Unstable, SimdReduce<R>>(input)
+}
+
+// Every 5 digit number is below this, so we can count instead of sort
+const FIVE_DIGIT_RANGE: usize = 100_000;
+
+// No sorting at all. Count how many times each value appears in each column,
+// then walk both histograms in lockstep pairing off the smallest remaining
+// values, which is exactly what zipping the sorted columns does.
+//
+// Clearing and walking two 100k entry histograms costs more than sorting the
+// real 1000 line input, it only wins on much bigger inputs.
+fn counting_i32<R: Reduce<i32>>(input: &[u8]) -> R::Output {
+    let mut left_counts = vec![0_u32; FIVE_DIGIT_RANGE];
+    let mut right_counts = vec![0_u32; FIVE_DIGIT_RANGE];
+
+    for line_bytes in input.chunks(14) {
+        left_counts[parse_5_digit_number_i32(&line_bytes[0..5]) as usize] += 1;
+        right_counts[parse_5_digit_number_i32(&line_bytes[8..13]) as usize] += 1;
     }
 
-    // Handle remaining elements
-    for i in (chunks * 4)..left_column.len() {
-        sum += (left_column[i] - right_column[i]).abs();
+    let mut sum = R::zero();
+    let mut left = 0;
+    let mut right = 0;
+
+    loop {
+        while left < FIVE_DIGIT_RANGE && left_counts[left] == 0 {
+            left += 1;
+        }
+        while right < FIVE_DIGIT_RANGE && right_counts[right] == 0 {
+            right += 1;
+        }
+        if left == FIVE_DIGIT_RANGE || right == FIVE_DIGIT_RANGE {
+            break;
+        }
+
+        // Pair off as many copies as both sides have
+        let pairs = left_counts[left].min(right_counts[right]);
+        let distance = (left as i32 - right as i32).abs();
+        for _ in 0..pairs {
+            sum = R::add(sum, distance);
+        }
+        left_counts[left] -= pairs;
+        right_counts[right] -= pairs;
     }
 
-    sum
+    R::finish(sum)
+}
+
+// LSD radix sort, a byte at a time. Doesn't care how wide the numbers are,
+// unlike counting. Flipping the sign bit makes negative numbers sort first.
+fn radix_sort_i32(values: &mut Vec<i32>) {
+    let mut scratch = vec![0_i32; values.len()];
+
+    for shift in (0..32).step_by(8) {
+        let digit = |v: i32| (((v as u32) ^ 0x8000_0000) >> shift) as usize & 0xff;
+
+        let mut counts = [0_usize; 256];
+        for &v in values.iter() {
+            counts[digit(v)] += 1;
+        }
+
+        // Every value has the same digit, this pass wouldn't move anything
+        if counts.contains(&values.len()) {
+            continue;
+        }
+
+        let mut offset = 0;
+        for count in counts.iter_mut() {
+            let c = *count;
+            *count = offset;
+            offset += c;
+        }
+
+        for &v in values.iter() {
+            let d = digit(v);
+            scratch[counts[d]] = v;
+            counts[d] += 1;
+        }
+
+        std::mem::swap(values, &mut scratch);
+    }
+}
+
+fn vec_i32_radix<R: Reduce<i32>>(input: &[u8]) -> R::Output {
+    distance::<i32, Scalar, Radix, R>(input)
+}
+
+fn vec_i32_simd_sort<R: Reduce<i32>>(input: &[u8]) -> R::Output {
+    distance::<i32, Scalar, SimdSort, R>(input)
+}
+
+fn vec_i32_unstable_swar<R: Reduce<i32>>(input: &[u8]) -> R::Output {
+    distance::<i32, Swar, Unstable, R>(input)
+}
+
+fn vec_i32_unstable_batch_parse<R: Reduce<i32>>(input: &[u8]) -> R::Output {
+    distance::<i32, BatchSimd, Unstable, R>(input)
 }
 
 // Heap version, actually about 2x slower than going into a vector
 // and then sorting the vector
-fn heap_i32(input: &[u8]) -> i32 {
-    let (left_column, right_column) = input
+fn heap_i32<R: Reduce<i32>>(input: &[u8]) -> R::Output {
+    let (mut left_column, mut right_column) = input
         .chunks(14)
         .map(|line_bytes| {
             let left = parse_5_digit_number_i32(&line_bytes[0..5]);
@@ -188,79 +482,424 @@ fn heap_i32(input: &[u8]) -> i32 {
         })
         .collect::<(BinaryHeap<i32>, BinaryHeap<i32>)>();
 
-    left_column
-        .into_iter_sorted()
-        .zip(right_column.into_iter_sorted())
-        .fold(0_i32, |acc, (left, right)| acc + (left - right).abs())
+    // Popping both dry pairs them off largest first
+    R::finish(
+        std::iter::from_fn(|| left_column.pop())
+            .zip(std::iter::from_fn(|| right_column.pop()))
+            .fold(R::zero(), |acc, (left, right)| {
+                R::add(acc, (left - right).abs())
+            }),
+    )
 }
 
+#[cfg(all(test, feature = "bench"))]
 extern crate test;
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use answers::expected;
+    use generate::{generate, ListOptions};
+    use pipeline::{Checked, Fold, OverflowError, Saturating, Widened};
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
-    // running 9 tests
-    // test tests::bench_output_heap_i32              ... bench:      26,341.04 ns/iter (+/- 2,374.00)
-    // test tests::bench_output_vec_i32               ... bench:      13,452.26 ns/iter (+/- 377.84)
-    // test tests::bench_output_vec_i32_simd_parse    ... bench:      11,901.90 ns/iter (+/- 369.81)
-    // test tests::bench_output_vec_i32_unstable      ... bench:      11,767.19 ns/iter (+/- 283.09)
-    // test tests::bench_output_vec_i32_unstable_simd ... bench:      11,757.39 ns/iter (+/- 335.71)
-    // test tests::bench_output_vec_i64               ... bench:      14,277.75 ns/iter (+/- 584.71)
-    // test tests::bench_parser                       ... bench:           0.31 ns/iter (+/- 0.01)
-    // test tests::bench_parser_simd_4                ... bench:           0.31 ns/iter (+/- 0.02)
-    // test tests::bench_parser_simd_8                ... bench:           0.31 ns/iter (+/- 0.01)
-    //
-    // test result: ok. 0 passed; 0 failed; 0 ignored; 9 measured; 0 filtered out; finished in 4.99s
+    // `cargo run --release --bin report -- input.txt` compares every variant,
+    // `report input.txt 100 vec_i32 csv` picks the runs, baseline and format.
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_parser(b: &mut Bencher) {
-        b.iter(|| parse_5_digit_number_i32(b"12345"));
+        b.iter(|| parse_5_digit_number_i32(test::black_box(b"12345")));
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_parser_simd_8(b: &mut Bencher) {
-        b.iter(|| parse_5_digit_number_i32x8_simd(b"12345"));
+        b.iter(|| parse_5_digit_number_i32x8_simd(test::black_box(b"12345")));
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_parser_simd_4(b: &mut Bencher) {
-        b.iter(|| parse_5_digit_number_i32x4_simd(b"12345"));
+        b.iter(|| parse_5_digit_number_i32x4_simd(test::black_box(b"12345")));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_parser_swar(b: &mut Bencher) {
+        b.iter(|| parse_number_swar(test::black_box(b"12345")));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_parse_columns_scalar(b: &mut Bencher) {
+        b.iter(|| {
+            test::black_box(INPUT)
+                .chunks(14)
+                .map(|line_bytes| {
+                    let left = parse_5_digit_number_i32(&line_bytes[0..5]);
+                    let right = parse_5_digit_number_i32(&line_bytes[8..13]);
+                    (left, right)
+                })
+                .collect::<(Vec<i32>, Vec<i32>)>()
+        });
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_parse_columns_batch_simd(b: &mut Bencher) {
+        assert_eq!(
+            parse_columns_batch_simd(INPUT),
+            try_parse_columns(INPUT).unwrap()
+        );
+        b.iter(|| parse_columns_batch_simd(test::black_box(INPUT)));
+    }
+
+    #[cfg(feature = "bench")]
+    fn bench_parse_columns_batch_simd_level(b: &mut Bencher, level: dispatch::Level) {
+        // Benches the detected level instead if this CPU can't run `level`
+        let level = level.min(dispatch::detected());
+        dispatch::with_level(level, || {
+            b.iter(|| parse_columns_batch_simd(test::black_box(INPUT)))
+        });
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_parse_columns_batch_simd_baseline(b: &mut Bencher) {
+        bench_parse_columns_batch_simd_level(b, dispatch::Level::Baseline);
     }
 
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_parse_columns_batch_simd_avx2(b: &mut Bencher) {
+        bench_parse_columns_batch_simd_level(b, dispatch::Level::Avx2);
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_parse_columns_batch_simd_avx512(b: &mut Bencher) {
+        bench_parse_columns_batch_simd_level(b, dispatch::Level::Avx512);
+    }
+
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_output_vec_i64(b: &mut Bencher) {
-        assert_eq!(936063, vec_i64(INPUT));
-        b.iter(|| vec_i64(INPUT));
+        assert_eq!(vec_i64::<Fold>(INPUT), expected::<i64>(INPUT, "part_1"));
+        b.iter(|| vec_i64::<Fold>(INPUT));
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_output_vec_i32(b: &mut Bencher) {
-        assert_eq!(936063, vec_i32(INPUT));
-        b.iter(|| vec_i32(INPUT));
+        assert_eq!(vec_i32::<Fold>(INPUT), expected::<i32>(INPUT, "part_1"));
+        b.iter(|| vec_i32::<Fold>(INPUT));
     }
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_output_vec_i32_simd_parse(b: &mut Bencher) {
-        assert_eq!(936063, vec_i32_simd_parse(INPUT));
-        b.iter(|| vec_i32_simd_parse(INPUT));
+        assert_eq!(
+            vec_i32_simd_parse::<Fold>(INPUT),
+            expected::<i32>(INPUT, "part_1")
+        );
+        b.iter(|| vec_i32_simd_parse::<Fold>(INPUT));
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_output_vec_i32_unstable(b: &mut Bencher) {
-        assert_eq!(936063, vec_i32_unstable(INPUT));
-        b.iter(|| vec_i32_unstable(INPUT));
+        assert_eq!(
+            vec_i32_unstable::<Fold>(INPUT),
+            expected::<i32>(INPUT, "part_1")
+        );
+        b.iter(|| vec_i32_unstable::<Fold>(INPUT));
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_output_vec_i32_unstable_simd(b: &mut Bencher) {
-        assert_eq!(936063, vec_i32_unstable_simd(INPUT));
-        b.iter(|| vec_i32_unstable_simd(INPUT));
+        assert_eq!(
+            vec_i32_unstable_simd::<Fold>(INPUT),
+            expected::<i32>(INPUT, "part_1")
+        );
+        b.iter(|| vec_i32_unstable_simd::<Fold>(INPUT));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_output_vec_i32_unstable_swar(b: &mut Bencher) {
+        assert_eq!(
+            vec_i32_unstable_swar::<Fold>(INPUT),
+            expected::<i32>(INPUT, "part_1")
+        );
+        b.iter(|| vec_i32_unstable_swar::<Fold>(test::black_box(INPUT)));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_output_counting_i32(b: &mut Bencher) {
+        assert_eq!(
+            counting_i32::<Fold>(INPUT),
+            expected::<i32>(INPUT, "part_1")
+        );
+        b.iter(|| counting_i32::<Fold>(INPUT));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_output_vec_i32_radix(b: &mut Bencher) {
+        assert_eq!(
+            vec_i32_radix::<Fold>(INPUT),
+            expected::<i32>(INPUT, "part_1")
+        );
+        b.iter(|| vec_i32_radix::<Fold>(INPUT));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_output_vec_i32_simd_sort(b: &mut Bencher) {
+        assert_eq!(
+            vec_i32_simd_sort::<Fold>(INPUT),
+            expected::<i32>(INPUT, "part_1")
+        );
+        b.iter(|| vec_i32_simd_sort::<Fold>(INPUT));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_output_vec_i32_unstable_batch_parse(b: &mut Bencher) {
+        assert_eq!(
+            vec_i32_unstable_batch_parse::<Fold>(INPUT),
+            expected::<i32>(INPUT, "part_1")
+        );
+        b.iter(|| vec_i32_unstable_batch_parse::<Fold>(test::black_box(INPUT)));
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_output_heap_i32(b: &mut Bencher) {
-        assert_eq!(936063, heap_i32(INPUT));
-        b.iter(|| heap_i32(INPUT));
+        assert_eq!(heap_i32::<Fold>(INPUT), expected::<i32>(INPUT, "part_1"));
+        b.iter(|| heap_i32::<Fold>(INPUT));
+    }
+
+    #[test]
+    fn test_try_parse_columns() {
+        let (left, right) = try_parse_columns(INPUT).expect("valid input");
+        assert_eq!(left.len(), 1000);
+        assert_eq!(right.len(), 1000);
+        assert_eq!(
+            &INPUT[0..14],
+            format!("{}   {}\n", left[0], right[0]).as_bytes()
+        );
+
+        assert_eq!(try_parse_columns(b""), Ok((vec![], vec![])));
+        assert_eq!(
+            try_parse_columns(b"12345   67890"),
+            Ok((vec![12345], vec![67890]))
+        );
+        assert_eq!(
+            try_parse_columns(b"12345   67890\n1234"),
+            Err(ParseError::TruncatedLine { line: 1 })
+        );
+        assert_eq!(
+            try_parse_columns(b"12a45   67890\n"),
+            Err(ParseError::NotADigit {
+                offset: 2,
+                byte: b'a'
+            })
+        );
+        assert_eq!(
+            try_parse_columns(b"12345   6789 \n"),
+            Err(ParseError::NotADigit {
+                offset: 12,
+                byte: b' '
+            })
+        );
+        assert_eq!(
+            try_parse_columns(b"12345 \t 67890\n"),
+            Err(ParseError::BadSeparator { offset: 5 })
+        );
+        assert_eq!(
+            try_parse_columns(b"12345   67890\r\n"),
+            Err(ParseError::MissingNewline { offset: 13 })
+        );
+    }
+
+    #[test]
+    fn test_parse_columns_batch_simd() {
+        // Every line count mod 4, with and without the final newline
+        for lines in 0..12 {
+            let list = generate(lines as u64, &ListOptions::new(lines));
+            let expected = try_parse_columns(&list.bytes).unwrap();
+
+            for level in dispatch::supported() {
+                dispatch::with_level(level, || {
+                    assert_eq!(parse_columns_batch_simd(&list.bytes), expected);
+                    assert_eq!(
+                        parse_columns_batch_simd(list.bytes.strip_suffix(b"\n").unwrap_or(&[])),
+                        expected,
+                        "{level:?}"
+                    );
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn test_variants_agree_on_generated() {
+        for (seed, lines) in [(0, 1), (1, 2), (2, 7), (3, 1000), (4, 10_000)] {
+            let list = generate(seed, &ListOptions::new(lines));
+            let expected = vec_i64::<Fold>(&list.bytes);
+
+            assert_eq!(expected, vec_i32::<Fold>(&list.bytes) as i64, "seed {seed}");
+            assert_eq!(expected, vec_i32_simd_parse::<Fold>(&list.bytes) as i64);
+            assert_eq!(expected, vec_i32_unstable::<Fold>(&list.bytes) as i64);
+            assert_eq!(expected, vec_i32_unstable_simd::<Fold>(&list.bytes) as i64);
+            assert_eq!(expected, heap_i32::<Fold>(&list.bytes) as i64);
+            assert_eq!(expected, counting_i32::<Fold>(&list.bytes) as i64);
+            assert_eq!(expected, vec_i32_radix::<Fold>(&list.bytes) as i64);
+            assert_eq!(expected, vec_i32_simd_sort::<Fold>(&list.bytes) as i64);
+            assert_eq!(
+                expected,
+                vec_i32_unstable_batch_parse::<Fold>(&list.bytes) as i64
+            );
+            assert_eq!(expected, vec_i32_unstable_swar::<Fold>(&list.bytes) as i64);
+        }
+    }
+
+    #[test]
+    fn test_parse_number_swar() {
+        for n in [0, 7, 10, 99, 12345, 99999, 1000000, 12345678, 99999999] {
+            assert_eq!(parse_number_swar(n.to_string().as_bytes()), n);
+        }
+        assert_eq!(parse_number_swar(b""), 0);
+        assert_eq!(parse_number_swar(b"00042"), 42);
+
+        // Past 8 digits it takes more than one pass
+        for n in [
+            100000000,
+            123456789,
+            999999999,
+            1234567890,
+            9876543210,
+            1234567890123456,
+            u64::MAX,
+        ] {
+            assert_eq!(parse_number_swar(n.to_string().as_bytes()), n);
+        }
+        assert_eq!(parse_number_swar(b"0000000042"), 42);
+
+        for line in INPUT.chunks(14) {
+            assert_eq!(
+                parse_number_swar(&line[8..13]) as i32,
+                parse_5_digit_number_i32(&line[8..13])
+            );
+        }
+    }
+
+    #[test]
+    fn test_accumulation_modes() {
+        // Every distance is 99999, so 30000 lines sum past i32::MAX
+        let overflowing = b"00000   99999\n".repeat(30_000);
+        let total = 30_000 * 99_999_i64;
+
+        macro_rules! check {
+            ($($variant:ident),*) => {
+                $(
+                    assert_eq!($variant::<Checked>(INPUT), Ok(expected(INPUT, "part_1")));
+                    assert_eq!($variant::<Checked>(&overflowing), Err(OverflowError));
+                    assert_eq!($variant::<Saturating>(&overflowing), i32::MAX);
+                    assert_eq!($variant::<Widened<i64>>(&overflowing), total);
+                    assert_eq!($variant::<Widened<i128>>(&overflowing), total as i128);
+                )*
+            };
+        }
+
+        check!(
+            vec_i32,
+            vec_i32_simd_parse,
+            vec_i32_unstable,
+            vec_i32_unstable_simd,
+            vec_i32_radix,
+            vec_i32_simd_sort,
+            vec_i32_unstable_swar,
+            vec_i32_unstable_batch_parse,
+            counting_i32,
+            heap_i32
+        );
+
+        assert_eq!(vec_i64::<Checked>(&overflowing), Ok(total));
+        assert_eq!(parallel::distance::<Checked>(&overflowing, 4), Ok(total));
+        assert_eq!(
+            parallel::distance::<Widened<i128>>(&overflowing, 4),
+            total as i128
+        );
+    }
+
+    #[test]
+    fn test_radix_sort_i32() {
+        let mut values = vec![5, -1, 70000, 0, i32::MIN, 3, i32::MAX, 5, -300];
+        let mut expected = values.clone();
+        expected.sort_unstable();
+
+        radix_sort_i32(&mut values);
+        assert_eq!(values, expected);
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_100k_vec_i32(b: &mut Bencher) {
+        let list = generate(1, &ListOptions::new(100_000));
+        b.iter(|| vec_i32::<Fold>(test::black_box(&list.bytes)));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_100k_vec_i32_unstable(b: &mut Bencher) {
+        let list = generate(1, &ListOptions::new(100_000));
+        b.iter(|| vec_i32_unstable::<Fold>(test::black_box(&list.bytes)));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_100k_vec_i32_unstable_swar(b: &mut Bencher) {
+        let list = generate(1, &ListOptions::new(100_000));
+        b.iter(|| vec_i32_unstable_swar::<Fold>(test::black_box(&list.bytes)));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_100k_vec_i32_unstable_simd(b: &mut Bencher) {
+        let list = generate(1, &ListOptions::new(100_000));
+        b.iter(|| vec_i32_unstable_simd::<Fold>(test::black_box(&list.bytes)));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_100k_heap_i32(b: &mut Bencher) {
+        let list = generate(1, &ListOptions::new(100_000));
+        b.iter(|| heap_i32::<Fold>(test::black_box(&list.bytes)));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_100k_counting_i32(b: &mut Bencher) {
+        let list = generate(1, &ListOptions::new(100_000));
+        b.iter(|| counting_i32::<Fold>(test::black_box(&list.bytes)));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_100k_vec_i32_radix(b: &mut Bencher) {
+        let list = generate(1, &ListOptions::new(100_000));
+        b.iter(|| vec_i32_radix::<Fold>(test::black_box(&list.bytes)));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_100k_vec_i32_simd_sort(b: &mut Bencher) {
+        let list = generate(1, &ListOptions::new(100_000));
+        b.iter(|| vec_i32_simd_sort::<Fold>(test::black_box(&list.bytes)));
     }
 }
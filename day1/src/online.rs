@@ -0,0 +1,331 @@
+//! Keep both answers up to date as pairs are appended, without sorting again.
+//!
+//! Part 2 only needs a count per value. For part 1 each column is kept as a
+//! Fenwick tree counting the values over the domain, which finds the `i`th
+//! smallest value and the rank of a value in `O(log domain)`.
+//!
+//! Appending a pair puts one value at rank `low` of its column and the other at
+//! rank `high >= low` of its own. Pairs ranked below `low` are untouched and
+//! ones at or above `high` just move up one rank, so only the pairs from `low`
+//! to `high` change:
+//!
+//! ```text
+//! before  (a[i], b[i])                     for i in low..high
+//! after   (new_a, b[low]), (a[i - 1], b[i]), ..., (a[high - 1], new_b)
+//! ```
+//!
+//! If `new_a >= new_b` every one of those pairs has `a` on top, before and
+//! after, so the total just grows by `new_a - new_b`. The same goes the other
+//! way round if `a[high - 1] <= b[low]`. Otherwise the ranks are split in
+//! half until each piece is one sided like that, see `Walk`.
+
+use std::collections::HashMap;
+
+use crate::{try_parse_columns, ParseError};
+
+/// Counts of a multiset of values in `0..domain`, as a Fenwick tree. Value `v`
+/// lives at index `v + 1`.
+struct Column {
+    counts: Vec<u32>,
+}
+
+impl Column {
+    fn new(domain: usize) -> Self {
+        Self {
+            counts: vec![0; domain + 1],
+        }
+    }
+
+    fn insert(&mut self, value: usize) {
+        let mut i = value + 1;
+        while i < self.counts.len() {
+            self.counts[i] += 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// How many values are below `value`
+    fn count_below(&self, value: usize) -> usize {
+        let mut count = 0;
+        let mut i = value.min(self.counts.len() - 1);
+        while i > 0 {
+            count += self.counts[i] as usize;
+            i &= i - 1;
+        }
+        count
+    }
+
+    /// The `rank`th smallest value, from 0. Walks down from the biggest
+    /// subtree, skipping every subtree that's all ranked below `rank`.
+    fn select(&self, rank: usize) -> usize {
+        let mut position = 0;
+        let mut remaining = rank;
+        let mut step = (self.counts.len() - 1)
+            .checked_ilog2()
+            .map_or(0, |log| 1 << log);
+
+        while step > 0 {
+            let next = position + step;
+            if next < self.counts.len() && (self.counts[next] as usize) <= remaining {
+                position = next;
+                remaining -= self.counts[next] as usize;
+            }
+            step /= 2;
+        }
+
+        // `position` is the last index with at most `rank` values up to it, so
+        // the value is at the next index, which is value `position`
+        position
+    }
+}
+
+/// Both columns before the insert, over the pairs that change. Pair `i` goes
+/// from `(a[i], b[i])` to `(a[i - 1], b[i])`, with the new value as `a[low - 1]`.
+struct Walk<'a> {
+    a: &'a Column,
+    b: &'a Column,
+}
+
+impl Walk<'_> {
+    /// Sum of the change in `|a - b|` over the pairs `start..end`, given
+    /// `a[start - 1]`, `a[end - 1]`, `b[start]` and `b[end - 1]`. Both columns
+    /// are sorted, so if the `a`s are all on one side of the `b`s across the
+    /// range each pair's change is one step of `a` and the sum telescopes.
+    /// Otherwise it's split in half.
+    fn change(
+        &self,
+        start: usize,
+        end: usize,
+        a_before: usize,
+        a_last: usize,
+        b_first: usize,
+        b_last: usize,
+    ) -> i64 {
+        if a_before >= b_last {
+            return a_before as i64 - a_last as i64;
+        }
+        if a_last <= b_first {
+            return a_last as i64 - a_before as i64;
+        }
+        if end - start == 1 {
+            // a_before < b < a_last
+            return (b_first - a_before) as i64 - (a_last - b_first) as i64;
+        }
+
+        let middle = (start + end) / 2;
+        let a_middle = self.a.select(middle - 1);
+        self.change(
+            start,
+            middle,
+            a_before,
+            a_middle,
+            b_first,
+            self.b.select(middle - 1),
+        ) + self.change(middle, end, a_middle, a_last, self.b.select(middle), b_last)
+    }
+}
+
+pub struct OnlineDistance {
+    left: Column,
+    right: Column,
+    domain: usize,
+    left_counts: HashMap<i32, u32>,
+    right_counts: HashMap<i32, u32>,
+    distance: i64,
+    similarity: i64,
+    len: usize,
+}
+
+impl OnlineDistance {
+    /// Empty lists of values in `0..domain`
+    pub fn new(domain: usize) -> Self {
+        Self {
+            left: Column::new(domain),
+            right: Column::new(domain),
+            domain,
+            left_counts: HashMap::new(),
+            right_counts: HashMap::new(),
+            distance: 0,
+            similarity: 0,
+            len: 0,
+        }
+    }
+
+    /// Empty lists of 5 digit values, like the real input
+    pub fn five_digit() -> Self {
+        Self::new(crate::FIVE_DIGIT_RANGE)
+    }
+
+    /// How much the total grows when `new_a` goes in at rank `low` of `a` and
+    /// `new_b` at rank `high >= low` of `b`, before either is inserted
+    fn distance_change(
+        (a, new_a, low): (&Column, usize, usize),
+        (b, new_b, high): (&Column, usize, usize),
+    ) -> i64 {
+        if low == high {
+            return new_a.abs_diff(new_b) as i64;
+        }
+
+        let a_last = a.select(high - 1);
+        let walk = Walk { a, b };
+        let change = walk.change(low, high, new_a, a_last, b.select(low), b.select(high - 1));
+        change + a_last.abs_diff(new_b) as i64
+    }
+
+    /// Append one line. Panics if either value is outside the domain.
+    pub fn push(&mut self, left: i32, right: i32) {
+        let (l, r) = (left as usize, right as usize);
+        assert!(
+            l < self.domain && r < self.domain,
+            "{left} or {right} is outside 0..{}",
+            self.domain
+        );
+
+        let left_rank = self.left.count_below(l);
+        let right_rank = self.right.count_below(r);
+        let left_side = (&self.left, l, left_rank);
+        let right_side = (&self.right, r, right_rank);
+
+        // Pairs are the same either way round, so whichever lands lower is `a`
+        self.distance += if left_rank <= right_rank {
+            Self::distance_change(left_side, right_side)
+        } else {
+            Self::distance_change(right_side, left_side)
+        };
+        self.left.insert(l);
+        self.right.insert(r);
+
+        // The new left value matches every right value so far and the new
+        // right value, and the new right value every earlier left value
+        let left_matches = self.right_counts.get(&left).copied().unwrap_or(0) + (l == r) as u32;
+        let right_matches = self.left_counts.get(&right).copied().unwrap_or(0);
+        self.similarity += left as i64 * left_matches as i64;
+        self.similarity += right as i64 * right_matches as i64;
+        *self.left_counts.entry(left).or_default() += 1;
+        *self.right_counts.entry(right).or_default() += 1;
+
+        self.len += 1;
+    }
+
+    /// Append every line of `input`, which must have the real input's layout
+    pub fn extend_from_bytes(&mut self, input: &[u8]) -> Result<(), ParseError> {
+        let (left_column, right_column) = try_parse_columns(input)?;
+
+        for (left, right) in left_column.into_iter().zip(right_column) {
+            self.push(left, right);
+        }
+
+        Ok(())
+    }
+
+    /// Part 1 for everything pushed so far
+    pub fn distance(&self) -> i64 {
+        self.distance
+    }
+
+    /// Part 2 for everything pushed so far
+    pub fn similarity(&self) -> i64 {
+        self.similarity
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "bench")]
+    extern crate test;
+    use super::*;
+    use crate::{
+        answers::expected,
+        generate::{generate, ListOptions},
+        INPUT,
+    };
+    #[cfg(feature = "bench")]
+    use test::Bencher;
+
+    fn sorted_distance(left: &[i32], right: &[i32]) -> i64 {
+        let mut left = left.to_vec();
+        let mut right = right.to_vec();
+        left.sort_unstable();
+        right.sort_unstable();
+        left.iter()
+            .zip(right.iter())
+            .map(|(l, r)| (l - r).abs() as i64)
+            .sum()
+    }
+
+    fn counted_similarity(left: &[i32], right: &[i32]) -> i64 {
+        left.iter()
+            .map(|l| *l as i64 * right.iter().filter(|r| *r == l).count() as i64)
+            .sum()
+    }
+
+    #[test]
+    fn test_input() {
+        let mut online = OnlineDistance::five_digit();
+        online.extend_from_bytes(INPUT).unwrap();
+
+        let (left, right) = try_parse_columns(INPUT).unwrap();
+        assert_eq!(online.len(), 1000);
+        assert_eq!(online.distance(), expected(INPUT, "part_1"));
+        assert_eq!(online.similarity(), counted_similarity(&left, &right));
+        assert_eq!(online.similarity(), expected(INPUT, "part_2"));
+    }
+
+    #[test]
+    fn test_every_prefix() {
+        for domain in [1, 2, 10, 37, 1000] {
+            let mut online = OnlineDistance::new(domain);
+            let (mut left, mut right) = (Vec::new(), Vec::new());
+            let mut seed = domain as u64;
+
+            for _ in 0..300 {
+                // Plenty of repeats and equal pairs in the small domains
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let l = (seed >> 33) as usize % domain;
+                let r = (seed >> 13) as usize % domain;
+
+                online.push(l as i32, r as i32);
+                left.push(l as i32);
+                right.push(r as i32);
+
+                assert_eq!(online.distance(), sorted_distance(&left, &right));
+                assert_eq!(online.similarity(), counted_similarity(&left, &right));
+            }
+        }
+    }
+
+    #[test]
+    fn test_generated() {
+        let list = generate(5, &ListOptions::new(2000));
+        let (left, right) = try_parse_columns(&list.bytes).unwrap();
+
+        let mut online = OnlineDistance::five_digit();
+        online.extend_from_bytes(&list.bytes).unwrap();
+
+        assert_eq!(online.distance(), sorted_distance(&left, &right));
+        assert_eq!(online.similarity(), counted_similarity(&left, &right));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_online_input(b: &mut Bencher) {
+        let (left, right) = try_parse_columns(INPUT).unwrap();
+        b.iter(|| {
+            let mut online = OnlineDistance::five_digit();
+            for (l, r) in left.iter().zip(right.iter()) {
+                online.push(*l, *r);
+            }
+            online.distance()
+        });
+    }
+}
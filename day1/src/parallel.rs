@@ -0,0 +1,292 @@
+use std::thread;
+
+use crate::{parse_5_digit_number_i64, pipeline::Reduce, LINE_LENGTH};
+
+/// Split `input` into at most `parts` pieces of roughly equal size, each ending
+/// on a line boundary
+fn split_lines(input: &[u8], parts: usize) -> Vec<&[u8]> {
+    let target = input.len().div_ceil(parts.max(1)).max(LINE_LENGTH);
+    let mut chunks = Vec::with_capacity(parts);
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        if rest.len() <= target {
+            chunks.push(rest);
+            break;
+        }
+
+        // Extend to the end of whichever line the cut lands in
+        let end = rest[target - 1..]
+            .iter()
+            .position(|c| *c == b'\n')
+            .map_or(rest.len(), |i| target + i);
+
+        let (chunk, next) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = next;
+    }
+
+    chunks
+}
+
+fn parse_columns(input: &[u8]) -> (Vec<i64>, Vec<i64>) {
+    input
+        .chunks(LINE_LENGTH)
+        .map(|line_bytes| {
+            let left = parse_5_digit_number_i64(&line_bytes[0..5]);
+            let right = parse_5_digit_number_i64(&line_bytes[8..13]);
+            (left, right)
+        })
+        .collect()
+}
+
+/// Merge two sorted runs into `out`
+fn merge(a: &[i64], b: &[i64], out: &mut [i64]) {
+    let (mut ia, mut ib) = (0, 0);
+
+    for slot in out.iter_mut() {
+        if ib == b.len() || (ia < a.len() && a[ia] <= b[ib]) {
+            *slot = a[ia];
+            ia += 1;
+        } else {
+            *slot = b[ib];
+            ib += 1;
+        }
+    }
+}
+
+// Below this many values a merge isn't worth handing to another thread
+const MIN_SPLIT_MERGE: usize = 4096;
+
+/// Merge two sorted runs into `out` across `threads` threads. The middle of the
+/// longer run is found in the other one by binary search, which splits both
+/// into halves that merge independently.
+fn merge_parallel(a: &[i64], b: &[i64], out: &mut [i64], threads: usize) {
+    if threads <= 1 || out.len() < MIN_SPLIT_MERGE {
+        merge(a, b, out);
+        return;
+    }
+
+    let (a, b) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let middle = a.len() / 2;
+    let split = b.partition_point(|value| *value < a[middle]);
+
+    let (a_low, a_high) = a.split_at(middle);
+    let (b_low, b_high) = b.split_at(split);
+    let (out_low, out_high) = out.split_at_mut(middle + split);
+
+    let half = threads / 2;
+    thread::scope(|s| {
+        s.spawn(|| merge_parallel(a_low, b_low, out_low, half));
+        merge_parallel(a_high, b_high, out_high, threads - half);
+    });
+}
+
+/// Merge sort across `threads` threads. Each thread sorts one run, then pairs
+/// of runs are merged in parallel until only one is left. Each merge is split
+/// again between the threads its level leaves idle, so the last few levels
+/// still use all of them.
+pub fn sort_i64(values: &mut Vec<i64>, threads: usize) {
+    let run = values.len().div_ceil(threads.max(1)).max(1);
+
+    thread::scope(|s| {
+        for chunk in values.chunks_mut(run) {
+            s.spawn(|| chunk.sort_unstable());
+        }
+    });
+
+    let mut scratch = vec![0; values.len()];
+    let mut width = run;
+
+    while width < values.len() {
+        let per_merge = (threads / values.len().div_ceil(width * 2)).max(1);
+
+        thread::scope(|s| {
+            for (from, to) in values.chunks(width * 2).zip(scratch.chunks_mut(width * 2)) {
+                s.spawn(move || {
+                    let (a, b) = from.split_at(width.min(from.len()));
+                    merge_parallel(a, b, to, per_merge);
+                });
+            }
+        });
+
+        std::mem::swap(values, &mut scratch);
+        width *= 2;
+    }
+}
+
+/// Same answer as `vec_i64`, with every stage spread over `threads` threads:
+/// the input is split on line boundaries and parsed in parallel, both columns
+/// are merge sorted in parallel, and the distances are summed in parallel.
+pub fn distance<R: Reduce<i64>>(input: &[u8], threads: usize) -> R::Output {
+    let threads = threads.max(1);
+
+    let parsed = thread::scope(|s| {
+        let handles = split_lines(input, threads)
+            .into_iter()
+            .map(|chunk| s.spawn(|| parse_columns(chunk)))
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    let lines = parsed.iter().map(|(left, _)| left.len()).sum();
+    let mut left_column = Vec::with_capacity(lines);
+    let mut right_column = Vec::with_capacity(lines);
+    for (left, right) in parsed {
+        left_column.extend(left);
+        right_column.extend(right);
+    }
+
+    // Each column gets half the threads
+    let half = threads.div_ceil(2);
+    thread::scope(|s| {
+        s.spawn(|| sort_i64(&mut left_column, half));
+        sort_i64(&mut right_column, half);
+    });
+
+    let run = lines.div_ceil(threads).max(1);
+    thread::scope(|s| {
+        let handles = left_column
+            .chunks(run)
+            .zip(right_column.chunks(run))
+            .map(|(left, right)| {
+                s.spawn(|| {
+                    left.iter()
+                        .zip(right.iter())
+                        .fold(R::zero(), |acc, (left, right)| {
+                            R::add(acc, (left - right).abs())
+                        })
+                })
+            })
+            .collect::<Vec<_>>();
+
+        R::finish(
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .fold(R::zero(), R::merge),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "bench")]
+    extern crate test;
+    use super::*;
+    use crate::{
+        answers::expected,
+        generate::{generate, ListOptions},
+        pipeline::Fold,
+        vec_i64, INPUT,
+    };
+    #[cfg(feature = "bench")]
+    use test::Bencher;
+
+    #[test]
+    fn test_split_lines() {
+        let list = generate(1, &ListOptions::new(101));
+
+        for parts in [1, 2, 3, 8, 200] {
+            let chunks = split_lines(&list.bytes, parts);
+
+            assert!(chunks.len() <= parts);
+            assert_eq!(chunks.concat(), list.bytes);
+            assert!(chunks.iter().all(|chunk| chunk.ends_with(b"\n")));
+        }
+
+        // The real input has no newline at the end
+        assert_eq!(split_lines(INPUT, 7).concat(), INPUT);
+        assert!(split_lines(b"", 4).is_empty());
+    }
+
+    #[test]
+    fn test_sort_i64() {
+        for threads in [1, 2, 3, 4, 7] {
+            for len in [0, 1, 2, 5, 100, 1001, 50_000] {
+                let mut values = (0..len)
+                    .map(|i| (i * 7919 % 1009) as i64 - 500)
+                    .collect::<Vec<_>>();
+                let mut expected = values.clone();
+                expected.sort_unstable();
+
+                sort_i64(&mut values, threads);
+                assert_eq!(values, expected, "threads {threads}, len {len}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_merge_parallel() {
+        // Plenty of values equal to where the runs get split
+        let a = (0..30_000).map(|i| i / 7).collect::<Vec<i64>>();
+        let b = (0..20_000).map(|i| i / 3 + 100).collect::<Vec<i64>>();
+        let mut expected = vec![0; a.len() + b.len()];
+        merge(&a, &b, &mut expected);
+
+        for threads in [1, 2, 3, 4, 8] {
+            for (a, b) in [(&a, &b), (&b, &a)] {
+                let mut out = vec![0; expected.len()];
+                merge_parallel(a, b, &mut out, threads);
+                assert_eq!(out, expected, "threads {threads}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_distance_matches_vec_i64() {
+        for threads in [1, 2, 3, 4, 8] {
+            assert_eq!(distance::<Fold>(INPUT, threads), expected(INPUT, "part_1"));
+
+            for (seed, lines) in [(0, 0), (1, 1), (2, 7), (3, 10_000)] {
+                let list = generate(seed, &ListOptions::new(lines));
+                assert_eq!(
+                    distance::<Fold>(&list.bytes, threads),
+                    vec_i64::<Fold>(&list.bytes),
+                    "threads {threads}, lines {lines}"
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "bench")]
+    fn bench_generated_1m(b: &mut Bencher, threads: usize) {
+        let list = generate(1, &ListOptions::new(1_000_000));
+        b.iter(|| distance::<Fold>(test::black_box(&list.bytes), threads));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_1m_vec_i64(b: &mut Bencher) {
+        let list = generate(1, &ListOptions::new(1_000_000));
+        b.iter(|| vec_i64::<Fold>(test::black_box(&list.bytes)));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_1m_parallel_1(b: &mut Bencher) {
+        bench_generated_1m(b, 1);
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_1m_parallel_2(b: &mut Bencher) {
+        bench_generated_1m(b, 2);
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_1m_parallel_4(b: &mut Bencher) {
+        bench_generated_1m(b, 4);
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_1m_parallel_8(b: &mut Bencher) {
+        bench_generated_1m(b, 8);
+    }
+}
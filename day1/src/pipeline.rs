@@ -0,0 +1,560 @@
+//! The day 1 solution split into its three stages so each can be swapped out
+//! independently: parse both columns, sort them, then reduce the sorted pairs.
+//!
+//! Every stage is a zero sized type picked at compile time, so something like
+//! `distance::<i32, Swar, Unstable, Fold>` compiles down to the same code as a
+//! hand written variant.
+
+use std::{
+    marker::PhantomData,
+    ops::{Add, Sub},
+};
+
+use crate::{
+    dispatch, parse_5_digit_number_i32, parse_5_digit_number_i32x4_simd,
+    parse_5_digit_number_i32x8_simd, parse_columns_batch_simd, parse_number_swar, radix_sort_i32,
+    LINE_LENGTH,
+};
+
+#[cfg(feature = "simd")]
+use crate::simd_sort;
+#[cfg(feature = "simd")]
+use std::simd::{i32x4, num::SimdInt};
+
+/// Integer types the columns can be stored as
+pub trait Number: Copy + Ord + Default + Send + Add<Output = Self> + Sub<Output = Self> {
+    fn from_i32(n: i32) -> Self;
+    fn abs(self) -> Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn saturating_add(self, other: Self) -> Self;
+}
+
+macro_rules! impl_number {
+    ($($t:ty),*) => {
+        $(
+            impl Number for $t {
+                #[inline(always)]
+                fn from_i32(n: i32) -> Self {
+                    n as $t
+                }
+
+                #[inline(always)]
+                fn abs(self) -> Self {
+                    <$t>::abs(self)
+                }
+
+                #[inline(always)]
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                #[inline(always)]
+                fn saturating_add(self, other: Self) -> Self {
+                    <$t>::saturating_add(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_number!(i32, i64, i128);
+
+/// Turns the input into the two columns
+pub trait Parse {
+    /// Parse one 5 digit number
+    fn parse(ascii_bytes: &[u8]) -> i32;
+
+    fn parse_columns<N: Number>(input: &[u8]) -> (Vec<N>, Vec<N>) {
+        parse_lines::<Self, N>(input)
+    }
+}
+
+#[inline(always)]
+fn parse_lines<P: Parse + ?Sized, N: Number>(input: &[u8]) -> (Vec<N>, Vec<N>) {
+    input
+        .chunks(LINE_LENGTH)
+        .map(|line_bytes| {
+            let left = N::from_i32(P::parse(&line_bytes[0..5]));
+            let right = N::from_i32(P::parse(&line_bytes[8..13]));
+            (left, right)
+        })
+        .collect()
+}
+
+dispatch::multiversion! {
+    /// `parse_lines` built for each SIMD level, for the SIMD parsers
+    fn parse_lines_simd<P: Parse, N: Number>(input: &[u8]) -> (Vec<N>, Vec<N>) {
+        parse_lines::<P, N>(input)
+    }
+}
+
+/// `parse_5_digit_number_i32`
+pub struct Scalar;
+/// `parse_5_digit_number_i32x8_simd`
+pub struct SimdI32x8;
+/// `parse_5_digit_number_i32x4_simd`
+pub struct SimdI32x4;
+/// `parse_number_swar`
+pub struct Swar;
+/// `parse_columns_batch_simd`, 4 lines at a time
+pub struct BatchSimd;
+
+impl Parse for Scalar {
+    #[inline(always)]
+    fn parse(ascii_bytes: &[u8]) -> i32 {
+        parse_5_digit_number_i32(ascii_bytes)
+    }
+}
+
+impl Parse for SimdI32x8 {
+    #[inline(always)]
+    fn parse(ascii_bytes: &[u8]) -> i32 {
+        parse_5_digit_number_i32x8_simd(ascii_bytes)
+    }
+
+    fn parse_columns<N: Number>(input: &[u8]) -> (Vec<N>, Vec<N>) {
+        parse_lines_simd::<Self, N>(input)
+    }
+}
+
+impl Parse for SimdI32x4 {
+    #[inline(always)]
+    fn parse(ascii_bytes: &[u8]) -> i32 {
+        parse_5_digit_number_i32x4_simd(ascii_bytes)
+    }
+
+    fn parse_columns<N: Number>(input: &[u8]) -> (Vec<N>, Vec<N>) {
+        parse_lines_simd::<Self, N>(input)
+    }
+}
+
+impl Parse for Swar {
+    #[inline(always)]
+    fn parse(ascii_bytes: &[u8]) -> i32 {
+        parse_number_swar(ascii_bytes) as i32
+    }
+}
+
+impl Parse for BatchSimd {
+    #[inline(always)]
+    fn parse(ascii_bytes: &[u8]) -> i32 {
+        parse_5_digit_number_i32(ascii_bytes)
+    }
+
+    fn parse_columns<N: Number>(input: &[u8]) -> (Vec<N>, Vec<N>) {
+        // For i32 the conversion is a no-op and the collect reuses the vector
+        let (left_column, right_column) = parse_columns_batch_simd(input);
+        (
+            left_column.into_iter().map(N::from_i32).collect(),
+            right_column.into_iter().map(N::from_i32).collect(),
+        )
+    }
+}
+
+/// Sorts one column ascending
+pub trait Sort<N> {
+    fn sort(values: &mut Vec<N>);
+}
+
+/// `sort`
+pub struct Stable;
+/// `sort_unstable`
+pub struct Unstable;
+/// `radix_sort_i32`, only for `i32`
+pub struct Radix;
+/// `simd_sort::sort_i32`, only for `i32`. Falls back to `sort_unstable`
+/// without the `simd` feature.
+pub struct SimdSort;
+
+impl<N: Number> Sort<N> for Stable {
+    fn sort(values: &mut Vec<N>) {
+        values.sort();
+    }
+}
+
+impl<N: Number> Sort<N> for Unstable {
+    fn sort(values: &mut Vec<N>) {
+        values.sort_unstable();
+    }
+}
+
+impl Sort<i32> for Radix {
+    fn sort(values: &mut Vec<i32>) {
+        radix_sort_i32(values);
+    }
+}
+
+impl Sort<i32> for SimdSort {
+    fn sort(values: &mut Vec<i32>) {
+        #[cfg(feature = "simd")]
+        simd_sort::sort_i32(values);
+        #[cfg(not(feature = "simd"))]
+        values.sort_unstable();
+    }
+}
+
+/// The distances overflowed the type they were being summed in
+#[derive(Debug, PartialEq, Eq)]
+pub struct OverflowError;
+
+impl std::fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "total distance overflowed")
+    }
+}
+
+impl std::error::Error for OverflowError {}
+
+/// Sums the distances into the answer. Variants that don't go through sorted
+/// columns (counting, heaps, threads) feed it one distance at a time with
+/// `add`, and `merge` partial sums.
+pub trait Reduce<N> {
+    type Sum: Copy + Send;
+    type Output;
+
+    fn zero() -> Self::Sum;
+    fn add(sum: Self::Sum, distance: N) -> Self::Sum;
+    fn merge(a: Self::Sum, b: Self::Sum) -> Self::Sum;
+    fn finish(sum: Self::Sum) -> Self::Output;
+
+    fn reduce(left_column: &[N], right_column: &[N]) -> Self::Output
+    where
+        N: Number,
+    {
+        Self::finish(
+            left_column
+                .iter()
+                .zip(right_column.iter())
+                .fold(Self::zero(), |acc, (left, right)| {
+                    Self::add(acc, (*left - *right).abs())
+                }),
+        )
+    }
+}
+
+/// Sum in the column type itself, like the original variants. Panics on
+/// overflow in debug and wraps in release.
+pub struct Fold;
+
+/// Sum in the column type, `Err(OverflowError)` if it overflows
+pub struct Checked;
+
+/// Sum in the column type, sticking at the max if it overflows
+pub struct Saturating;
+
+/// Sum in the wider type `W`, e.g. `Widened<i64>` for `i32` columns
+pub struct Widened<W>(PhantomData<W>);
+
+impl<N: Number> Reduce<N> for Fold {
+    type Sum = N;
+    type Output = N;
+
+    #[inline(always)]
+    fn zero() -> N {
+        N::default()
+    }
+
+    #[inline(always)]
+    fn add(sum: N, distance: N) -> N {
+        sum + distance
+    }
+
+    fn merge(a: N, b: N) -> N {
+        a + b
+    }
+
+    fn finish(sum: N) -> N {
+        sum
+    }
+}
+
+impl<N: Number> Reduce<N> for Checked {
+    type Sum = Option<N>;
+    type Output = Result<N, OverflowError>;
+
+    #[inline(always)]
+    fn zero() -> Option<N> {
+        Some(N::default())
+    }
+
+    #[inline(always)]
+    fn add(sum: Option<N>, distance: N) -> Option<N> {
+        sum?.checked_add(distance)
+    }
+
+    fn merge(a: Option<N>, b: Option<N>) -> Option<N> {
+        a?.checked_add(b?)
+    }
+
+    fn finish(sum: Option<N>) -> Result<N, OverflowError> {
+        sum.ok_or(OverflowError)
+    }
+}
+
+impl<N: Number> Reduce<N> for Saturating {
+    type Sum = N;
+    type Output = N;
+
+    #[inline(always)]
+    fn zero() -> N {
+        N::default()
+    }
+
+    #[inline(always)]
+    fn add(sum: N, distance: N) -> N {
+        sum.saturating_add(distance)
+    }
+
+    fn merge(a: N, b: N) -> N {
+        a.saturating_add(b)
+    }
+
+    fn finish(sum: N) -> N {
+        sum
+    }
+}
+
+impl<N: Number, W: Number + From<N>> Reduce<N> for Widened<W> {
+    type Sum = W;
+    type Output = W;
+
+    #[inline(always)]
+    fn zero() -> W {
+        W::default()
+    }
+
+    #[inline(always)]
+    fn add(sum: W, distance: N) -> W {
+        sum + W::from(distance)
+    }
+
+    fn merge(a: W, b: W) -> W {
+        a + b
+    }
+
+    fn finish(sum: W) -> W {
+        sum
+    }
+}
+
+/// Reduces with `R`, taking the distances four at a time in an `i32x4` with a
+/// separate sum per lane that are merged at the end. Just `R` without the
+/// `simd` feature.
+pub struct SimdReduce<R>(PhantomData<R>);
+
+impl<R: Reduce<i32>> Reduce<i32> for SimdReduce<R> {
+    type Sum = R::Sum;
+    type Output = R::Output;
+
+    #[inline(always)]
+    fn zero() -> R::Sum {
+        R::zero()
+    }
+
+    #[inline(always)]
+    fn add(sum: R::Sum, distance: i32) -> R::Sum {
+        R::add(sum, distance)
+    }
+
+    fn merge(a: R::Sum, b: R::Sum) -> R::Sum {
+        R::merge(a, b)
+    }
+
+    fn finish(sum: R::Sum) -> R::Output {
+        R::finish(sum)
+    }
+
+    #[cfg(feature = "simd")]
+    fn reduce(left_column: &[i32], right_column: &[i32]) -> R::Output {
+        const LANES: usize = 4;
+
+        let length = left_column.len().min(right_column.len());
+        let left_chunks = left_column[..length].chunks_exact(LANES);
+        let right_chunks = right_column[..length].chunks_exact(LANES);
+        let remainder = left_chunks.remainder().iter().zip(right_chunks.remainder());
+
+        let mut sums = [R::zero(); LANES];
+
+        for (left, right) in left_chunks.zip(right_chunks) {
+            let distances = (i32x4::from_slice(left) - i32x4::from_slice(right)).abs();
+
+            for (sum, distance) in sums.iter_mut().zip(distances.to_array()) {
+                *sum = R::add(*sum, distance);
+            }
+        }
+
+        let sum = remainder.fold(
+            sums.into_iter().fold(R::zero(), R::merge),
+            |sum, (left, right)| R::add(sum, (left - right).abs()),
+        );
+
+        R::finish(sum)
+    }
+}
+
+/// Run the three stages over `input`
+pub fn distance<N, P, S, R>(input: &[u8]) -> R::Output
+where
+    N: Number,
+    P: Parse,
+    S: Sort<N>,
+    R: Reduce<N>,
+{
+    let (mut left_column, mut right_column) = P::parse_columns::<N>(input);
+
+    S::sort(&mut left_column);
+    S::sort(&mut right_column);
+
+    R::reduce(&left_column, &right_column)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "bench")]
+    extern crate test;
+    use super::*;
+    use crate::{
+        answers::expected,
+        generate::{generate, ListOptions},
+        INPUT,
+    };
+
+    // Every combination of parser and sort, for each integer type they support
+    macro_rules! for_each_pipeline {
+        ($m:ident) => {
+            for_each_pipeline!(@number $m, i32, i32, [Radix, SimdSort]);
+            for_each_pipeline!(@number $m, i64, i64, []);
+            for_each_pipeline!(@number $m, i128, i128, []);
+        };
+        (@number $m:ident, $n:ident, $t:ty, [$($extra:ident),*]) => {
+            for_each_pipeline!(@parse $m, $n, $t, [Stable, Unstable $(, $extra)*], Scalar);
+            for_each_pipeline!(@parse $m, $n, $t, [Stable, Unstable $(, $extra)*], SimdI32x8);
+            for_each_pipeline!(@parse $m, $n, $t, [Stable, Unstable $(, $extra)*], SimdI32x4);
+            for_each_pipeline!(@parse $m, $n, $t, [Stable, Unstable $(, $extra)*], Swar);
+            for_each_pipeline!(@parse $m, $n, $t, [Stable, Unstable $(, $extra)*], BatchSimd);
+        };
+        (@parse $m:ident, $n:ident, $t:ty, [$($sort:ident),*], $parse:ident) => {
+            $( $m!($n, $t, $parse, $sort); )*
+        };
+    }
+
+    #[test]
+    fn test_every_pipeline_on_input() {
+        macro_rules! check {
+            ($n:ident, $t:ty, $parse:ident, $sort:ident) => {
+                assert_eq!(
+                    distance::<$t, $parse, $sort, Fold>(INPUT),
+                    expected(INPUT, "part_1"),
+                    "{} {} {}",
+                    stringify!($n),
+                    stringify!($parse),
+                    stringify!($sort)
+                );
+            };
+        }
+
+        for_each_pipeline!(check);
+    }
+
+    #[test]
+    fn test_simd_parsers_every_level() {
+        let list = generate(6, &ListOptions::new(500));
+        let generated = distance::<i64, Scalar, Unstable, Fold>(&list.bytes);
+
+        let answer = expected::<i32>(INPUT, "part_1");
+
+        for level in dispatch::supported() {
+            dispatch::with_level(level, || {
+                assert_eq!(distance::<i32, SimdI32x8, Unstable, Fold>(INPUT), answer);
+                assert_eq!(distance::<i32, SimdI32x4, Unstable, Fold>(INPUT), answer);
+                assert_eq!(
+                    distance::<i64, SimdI32x8, Unstable, Fold>(&list.bytes),
+                    generated
+                );
+                assert_eq!(
+                    distance::<i64, SimdI32x4, Unstable, Fold>(&list.bytes),
+                    generated
+                );
+            });
+        }
+    }
+
+    #[test]
+    fn test_every_pipeline_on_generated() {
+        for (seed, lines) in [(0, 0), (1, 1), (2, 5), (3, 1000)] {
+            let list = generate(seed, &ListOptions::new(lines));
+            let expected = distance::<i64, Scalar, Stable, Fold>(&list.bytes);
+
+            macro_rules! check {
+                ($n:ident, $t:ty, $parse:ident, $sort:ident) => {
+                    assert_eq!(
+                        distance::<$t, $parse, $sort, Fold>(&list.bytes) as i64,
+                        expected,
+                        "{} {} {} on {lines} lines",
+                        stringify!($n),
+                        stringify!($parse),
+                        stringify!($sort)
+                    );
+                };
+            }
+
+            for_each_pipeline!(check);
+        }
+    }
+
+    #[test]
+    fn test_simd_reduce() {
+        for lines in [0, 1, 3, 4, 5, 1001] {
+            let list = generate(lines as u64, &ListOptions::new(lines));
+
+            assert_eq!(
+                distance::<i32, Scalar, Unstable, SimdReduce<Fold>>(&list.bytes),
+                distance::<i32, Scalar, Unstable, Fold>(&list.bytes),
+                "{lines} lines"
+            );
+        }
+
+        // Every distance is 99999, so 30000 lines sum past i32::MAX
+        let overflowing = b"00000   99999\n".repeat(30_000);
+        assert_eq!(
+            distance::<i32, Scalar, Unstable, SimdReduce<Checked>>(&overflowing),
+            Err(OverflowError)
+        );
+        assert_eq!(
+            distance::<i32, Scalar, Unstable, SimdReduce<Saturating>>(&overflowing),
+            i32::MAX
+        );
+        assert_eq!(
+            distance::<i32, Scalar, Unstable, SimdReduce<Widened<i64>>>(&overflowing),
+            30_000 * 99_999
+        );
+    }
+
+    // One bench per combination, named `pipeline::tests::<number>::<parse>::<sort>`
+    macro_rules! bench_pipelines {
+        ($n:ident: $t:ty, [$($parse:ident),*], $sorts:tt) => {
+            #[cfg(feature = "bench")]
+            #[allow(non_snake_case)]
+            mod $n {
+                $( bench_pipelines!(@parse $t, $parse, $sorts); )*
+            }
+        };
+        (@parse $t:ty, $parse:ident, [$($sort:ident),*]) => {
+            #[allow(non_snake_case)]
+            mod $parse {
+                use crate::{pipeline::*, INPUT};
+
+                $(
+                    #[bench]
+                    fn $sort(b: &mut test::Bencher) {
+                        b.iter(|| distance::<$t, $parse, $sort, Fold>(test::black_box(INPUT)));
+                    }
+                )*
+            }
+        };
+    }
+
+    bench_pipelines!(i32: i32, [Scalar, SimdI32x8, SimdI32x4, Swar, BatchSimd], [Stable, Unstable, Radix, SimdSort]);
+    bench_pipelines!(i64: i64, [Scalar, SimdI32x8, SimdI32x4, Swar, BatchSimd], [Stable, Unstable]);
+    bench_pipelines!(i128: i128, [Scalar, SimdI32x8, SimdI32x4, Swar, BatchSimd], [Stable, Unstable]);
+}
@@ -0,0 +1,155 @@
+//! Deliberately naive O(n²) solvers that every optimised variant is checked
+//! against. They share nothing with the real code, not even the parser.
+
+use crate::{
+    answers::expected,
+    columns::Columns,
+    explain::Explanation,
+    generate::{generate, ListOptions},
+    online::OnlineDistance,
+    parallel,
+    pipeline::{Checked, Widened},
+    report::VARIANTS,
+    stream, vec_i64, INPUT,
+};
+
+fn columns(input: &[u8]) -> (Vec<i64>, Vec<i64>) {
+    let text = std::str::from_utf8(input).unwrap();
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        left.push(fields.next().unwrap().parse().unwrap());
+        right.push(fields.next().unwrap().parse().unwrap());
+    }
+
+    (left, right)
+}
+
+fn take_min(values: &mut Vec<i64>) -> i64 {
+    let mut min = 0;
+    for i in 1..values.len() {
+        if values[i] < values[min] {
+            min = i;
+        }
+    }
+    values.remove(min)
+}
+
+/// Pair off the smallest remaining value on each side until both are empty
+pub fn part_1(input: &[u8]) -> i64 {
+    let (mut left, mut right) = columns(input);
+    let mut total = 0;
+
+    while !left.is_empty() {
+        total += (take_min(&mut left) - take_min(&mut right)).abs();
+    }
+
+    total
+}
+
+/// Scan the whole right column for every left value
+pub fn part_2(input: &[u8]) -> i64 {
+    let (left, right) = columns(input);
+
+    left.iter()
+        .map(|l| l * right.iter().filter(|r| *r == l).count() as i64)
+        .sum()
+}
+
+/// Every part 1 and part 2 solver in the crate agrees with the reference
+fn assert_matches_reference(input: &[u8], case: &str) {
+    let expected = part_1(input);
+
+    assert_eq!(vec_i64::<Checked>(input), Ok(expected), "vec_i64 on {case}");
+
+    for variant in VARIANTS.iter().filter(|variant| variant.available()) {
+        assert_eq!(variant.call(input), expected, "{} on {case}", variant.name);
+    }
+
+    for threads in [1, 3] {
+        assert_eq!(
+            parallel::distance::<Checked>(input, threads),
+            Ok(expected),
+            "parallel with {threads} threads on {case}"
+        );
+    }
+
+    assert_eq!(
+        stream::distance::<Widened<i64>>(input).unwrap(),
+        expected,
+        "stream on {case}"
+    );
+    assert_eq!(
+        Explanation::from_bytes(input).unwrap().total(),
+        expected,
+        "explain on {case}"
+    );
+
+    // An empty input has no columns at all
+    if !input.is_empty() {
+        assert_eq!(
+            Columns::parse(input).unwrap().distance(0, 1),
+            Ok(expected as u128),
+            "columns on {case}"
+        );
+    }
+
+    let mut online = OnlineDistance::five_digit();
+    online.extend_from_bytes(input).unwrap();
+    assert_eq!(online.distance(), expected, "online on {case}");
+    assert_eq!(
+        online.similarity(),
+        part_2(input),
+        "online part 2 on {case}"
+    );
+}
+
+#[test]
+fn test_reference_example() {
+    let input = b"3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+    assert_eq!(part_1(input), 11);
+    assert_eq!(part_2(input), 31);
+}
+
+#[test]
+fn test_known_answers() {
+    assert_eq!(part_1(INPUT), expected::<i64>(INPUT, "part_1"));
+    assert_eq!(part_2(INPUT), expected::<i64>(INPUT, "part_2"));
+}
+
+#[test]
+fn test_edge_cases() {
+    assert_matches_reference(b"", "an empty input");
+    assert_matches_reference(b"12345   54321", "a single line without a newline");
+    assert_matches_reference(b"12345   54321\n", "a single line");
+    assert_matches_reference(b"00000   99999\n99999   00000\n", "the extremes");
+    assert_matches_reference(&b"77777   77777\n".repeat(100), "all equal values");
+    assert_matches_reference(
+        &b"11111   22222\n22222   11111\n".repeat(50),
+        "swapped duplicates",
+    );
+}
+
+#[test]
+fn test_generated() {
+    for seed in 0..20 {
+        let lines = [1, 2, 3, 4, 5, 8, 13, 100][seed as usize % 8];
+        let list = generate(seed, &ListOptions::new(lines));
+        assert_matches_reference(&list.bytes, &format!("seed {seed}, {lines} lines"));
+
+        // Lots of repeats for part 2 to count
+        let list = generate(
+            seed,
+            &ListOptions {
+                duplicate_ratio: 0.7,
+                ..ListOptions::new(lines * 3)
+            },
+        );
+        assert_matches_reference(
+            &list.bytes,
+            &format!("seed {seed}, {} lines with duplicates", lines * 3),
+        );
+    }
+}
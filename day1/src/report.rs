@@ -0,0 +1,198 @@
+//! Every part 1 variant, for `cargo run --release --bin report` to time on one
+//! input with [`aoc_shared::report`].
+
+use std::hint::black_box;
+
+pub use aoc_shared::report::{time, Report, Stats};
+
+use crate::{
+    counting_i32,
+    dispatch::{self, Level},
+    heap_i32,
+    online::OnlineDistance,
+    parse_5_digit_number_i32,
+    pipeline::Widened,
+    vec_i32, vec_i32_radix, vec_i32_simd_parse, vec_i32_simd_sort, vec_i32_unstable,
+    vec_i32_unstable_batch_parse, vec_i32_unstable_simd, vec_i32_unstable_swar, vec_i64,
+    LINE_LENGTH,
+};
+
+/// A named part 1 solver. They all trust the input to be in the real input's
+/// layout.
+pub struct Variant {
+    pub name: &'static str,
+    pub run: fn(&[u8]) -> i64,
+    /// Dispatch level forced while it runs, or the detected one if `None`
+    pub level: Option<Level>,
+}
+
+impl Variant {
+    const fn new(name: &'static str, run: fn(&[u8]) -> i64) -> Self {
+        Self {
+            name,
+            run,
+            level: None,
+        }
+    }
+
+    const fn at(self, level: Level) -> Self {
+        Self {
+            level: Some(level),
+            ..self
+        }
+    }
+
+    /// Whether this CPU can run it
+    pub fn available(&self) -> bool {
+        self.level.is_none_or(|level| level <= dispatch::detected())
+    }
+
+    /// Run it on `input`. Panics if it isn't `available`.
+    pub fn call(&self, input: &[u8]) -> i64 {
+        match self.level {
+            Some(level) => dispatch::with_level(level, || (self.run)(input)),
+            None => (self.run)(input),
+        }
+    }
+}
+
+/// Push every line in turn, which is what `OnlineDistance` is for, so this is
+/// what it costs over sorting once
+fn online(input: &[u8]) -> i64 {
+    let mut online = OnlineDistance::five_digit();
+    for line in input.chunks(LINE_LENGTH) {
+        online.push(
+            parse_5_digit_number_i32(&line[0..5]),
+            parse_5_digit_number_i32(&line[8..13]),
+        );
+    }
+    online.distance()
+}
+
+/// Every variant the report knows about
+pub const VARIANTS: &[Variant] = &[
+    Variant::new("vec_i64", vec_i64::<Widened<i64>>),
+    Variant::new("vec_i32", vec_i32::<Widened<i64>>),
+    Variant::new("vec_i32_simd_parse", vec_i32_simd_parse::<Widened<i64>>),
+    Variant::new("vec_i32_unstable", vec_i32_unstable::<Widened<i64>>),
+    Variant::new(
+        "vec_i32_unstable_simd",
+        vec_i32_unstable_simd::<Widened<i64>>,
+    ),
+    Variant::new("vec_i32_radix", vec_i32_radix::<Widened<i64>>),
+    Variant::new("vec_i32_simd_sort", vec_i32_simd_sort::<Widened<i64>>),
+    Variant::new(
+        "vec_i32_unstable_swar",
+        vec_i32_unstable_swar::<Widened<i64>>,
+    ),
+    Variant::new(
+        "vec_i32_unstable_batch_parse",
+        vec_i32_unstable_batch_parse::<Widened<i64>>,
+    ),
+    Variant::new(
+        "vec_i32_unstable_batch_parse_baseline",
+        vec_i32_unstable_batch_parse::<Widened<i64>>,
+    )
+    .at(Level::Baseline),
+    Variant::new(
+        "vec_i32_unstable_batch_parse_avx2",
+        vec_i32_unstable_batch_parse::<Widened<i64>>,
+    )
+    .at(Level::Avx2),
+    Variant::new(
+        "vec_i32_unstable_batch_parse_avx512",
+        vec_i32_unstable_batch_parse::<Widened<i64>>,
+    )
+    .at(Level::Avx512),
+    Variant::new("counting_i32", counting_i32::<Widened<i64>>),
+    Variant::new("heap_i32", heap_i32::<Widened<i64>>),
+    Variant::new("online", online),
+];
+
+pub fn variant(name: &str) -> Option<&'static Variant> {
+    VARIANTS.iter().find(|variant| variant.name == name)
+}
+
+/// Time `runs` runs of `variant` on `input`, after one untimed warm up run.
+/// Panics if it isn't `available`.
+pub fn measure(variant: &Variant, input: &[u8], runs: usize) -> Stats {
+    time(variant.name, input.len(), runs, || {
+        variant.call(black_box(input))
+    })
+}
+
+/// Measure every variant this CPU can run, relative to the one named
+/// `baseline`. Returns `None` if there's no such variant.
+pub fn run(input: &[u8], runs: usize, baseline: &str) -> Option<Report<i64>> {
+    let variants = VARIANTS
+        .iter()
+        .filter(|variant| variant.available())
+        .collect::<Vec<_>>();
+    let baseline = variants
+        .iter()
+        .position(|variant| variant.name == baseline)?;
+    let (answers, rows) = variants
+        .iter()
+        .map(|variant| (variant.call(input), measure(variant, input, runs)))
+        .unzip();
+
+    Some(Report {
+        rows,
+        answers,
+        baseline,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{answers::expected, INPUT};
+
+    #[test]
+    fn test_variants_agree() {
+        for variant in VARIANTS.iter().filter(|variant| variant.available()) {
+            assert_eq!(
+                variant.call(INPUT),
+                expected(INPUT, "part_1"),
+                "{}",
+                variant.name
+            );
+        }
+        assert!(variant("vec_i32_radix").is_some());
+        assert!(variant("nope").is_none());
+        assert!(variant("vec_i32_unstable_batch_parse_baseline")
+            .unwrap()
+            .available());
+    }
+
+    #[test]
+    fn test_report() {
+        assert!(run(INPUT, 1, "nope").is_none());
+
+        let available = VARIANTS
+            .iter()
+            .filter(|variant| variant.available())
+            .count();
+        let report = run(INPUT, 3, "vec_i32").unwrap();
+        assert_eq!(report.rows.len(), available);
+        assert!(report
+            .answers
+            .iter()
+            .all(|answer| *answer == expected::<i64>(INPUT, "part_1")));
+        assert_eq!(report.relative_speed(&report.rows[report.baseline]), 1.0);
+
+        let mut markdown = Vec::new();
+        report.write_markdown(&mut markdown).unwrap();
+        let markdown = String::from_utf8(markdown).unwrap();
+        assert!(markdown.starts_with("| variant | median | min | stddev | MB/s | vs vec_i32 |\n"));
+        assert_eq!(markdown.lines().count(), available + 2);
+        assert!(markdown.contains("| vec_i32 |") && markdown.contains("| 1.00x |"));
+
+        let mut csv = Vec::new();
+        report.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), available + 1);
+        assert!(csv.lines().nth(2).unwrap().starts_with("vec_i32,3,"));
+        assert!(csv.lines().nth(2).unwrap().ends_with(",1.000"));
+    }
+}
@@ -0,0 +1,212 @@
+use std::simd::{cmp::SimdOrd, i32x8, mask32x8, simd_swizzle, Select};
+
+const LANES: usize = 8;
+
+// Below this the network setup and padding isn't worth it
+const SCALAR_THRESHOLD: usize = 64;
+
+/// One compare-exchange stage of a sorting network across the lanes of a
+/// single vector. Each lane is compared with the lane `partner` picks and keeps
+/// the max where `take_max` is set, otherwise the min.
+#[inline(always)]
+fn compare_exchange(v: i32x8, partner: i32x8, take_max: mask32x8) -> i32x8 {
+    take_max.select(v.simd_max(partner), v.simd_min(partner))
+}
+
+/// Sort a bitonic vector (ascending then descending, or rotated) ascending.
+/// These are the last three stages of the full bitonic sort.
+#[inline(always)]
+fn bitonic_merge_i32x8(v: i32x8) -> i32x8 {
+    let v = compare_exchange(
+        v,
+        simd_swizzle!(v, [4, 5, 6, 7, 0, 1, 2, 3]),
+        mask32x8::from_array([false, false, false, false, true, true, true, true]),
+    );
+    let v = compare_exchange(
+        v,
+        simd_swizzle!(v, [2, 3, 0, 1, 6, 7, 4, 5]),
+        mask32x8::from_array([false, false, true, true, false, false, true, true]),
+    );
+    compare_exchange(
+        v,
+        simd_swizzle!(v, [1, 0, 3, 2, 5, 4, 7, 6]),
+        mask32x8::from_array([false, true, false, true, false, true, false, true]),
+    )
+}
+
+/// Bitonic sorting network over the 8 lanes of one vector
+#[inline(always)]
+fn sort_i32x8(v: i32x8) -> i32x8 {
+    // Build sorted pairs going alternately up and down
+    let v = compare_exchange(
+        v,
+        simd_swizzle!(v, [1, 0, 3, 2, 5, 4, 7, 6]),
+        mask32x8::from_array([false, true, true, false, false, true, true, false]),
+    );
+    // Then runs of 4 going up then down
+    let v = compare_exchange(
+        v,
+        simd_swizzle!(v, [2, 3, 0, 1, 6, 7, 4, 5]),
+        mask32x8::from_array([false, false, true, true, true, true, false, false]),
+    );
+    let v = compare_exchange(
+        v,
+        simd_swizzle!(v, [1, 0, 3, 2, 5, 4, 7, 6]),
+        mask32x8::from_array([false, true, false, true, true, false, true, false]),
+    );
+    // Which leaves the whole vector bitonic
+    bitonic_merge_i32x8(v)
+}
+
+/// Merge two sorted vectors into the low and high 8 of the 16 values.
+/// Reversing `b` makes the pair bitonic, so a single min/max splits them in half
+/// and each half is itself bitonic.
+#[inline(always)]
+fn merge_i32x8(a: i32x8, b: i32x8) -> (i32x8, i32x8) {
+    let b = b.reverse();
+    let low = a.simd_min(b);
+    let high = a.simd_max(b);
+    (bitonic_merge_i32x8(low), bitonic_merge_i32x8(high))
+}
+
+/// Merge two sorted runs, both a multiple of 8 long, into `out`. Keeps the top
+/// 8 values in a register and feeds it the next block from whichever run has
+/// the smaller head.
+fn merge_runs(a: &[i32], b: &[i32], out: &mut [i32]) {
+    if a.is_empty() || b.is_empty() {
+        out.copy_from_slice(if a.is_empty() { b } else { a });
+        return;
+    }
+
+    let (low, mut high) = merge_i32x8(i32x8::from_slice(a), i32x8::from_slice(b));
+    low.copy_to_slice(&mut out[0..LANES]);
+
+    let mut ia = LANES;
+    let mut ib = LANES;
+    let mut o = LANES;
+
+    while ia < a.len() || ib < b.len() {
+        let next = if ib == b.len() || (ia < a.len() && a[ia] <= b[ib]) {
+            ia += LANES;
+            i32x8::from_slice(&a[ia - LANES..ia])
+        } else {
+            ib += LANES;
+            i32x8::from_slice(&b[ib - LANES..ib])
+        };
+
+        let (low, next_high) = merge_i32x8(high, next);
+        low.copy_to_slice(&mut out[o..o + LANES]);
+        high = next_high;
+        o += LANES;
+    }
+
+    high.copy_to_slice(&mut out[o..o + LANES]);
+}
+
+/// Bottom up merge sort. Blocks of 8 are sorted with a bitonic network in a
+/// single register, then runs are merged with a bitonic merge network.
+/// Small inputs fall back to `sort_unstable`.
+pub fn sort_i32(values: &mut Vec<i32>) {
+    let len = values.len();
+
+    if len < SCALAR_THRESHOLD {
+        values.sort_unstable();
+        return;
+    }
+
+    // Pad out to whole vectors, the padding sorts to the end and is cut off again
+    values.resize(len.next_multiple_of(LANES), i32::MAX);
+
+    for chunk in values.chunks_exact_mut(LANES) {
+        sort_i32x8(i32x8::from_slice(chunk)).copy_to_slice(chunk);
+    }
+
+    let mut scratch = vec![0; values.len()];
+    let mut width = LANES;
+
+    while width < values.len() {
+        for (from, to) in values.chunks(width * 2).zip(scratch.chunks_mut(width * 2)) {
+            let (a, b) = from.split_at(width.min(from.len()));
+            merge_runs(a, b, to);
+        }
+
+        std::mem::swap(values, &mut scratch);
+        width *= 2;
+    }
+
+    values.truncate(len);
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "bench")]
+    extern crate test;
+    use super::*;
+    use crate::generate::SplitMix64;
+    #[cfg(feature = "bench")]
+    use test::Bencher;
+
+    fn random_values(seed: u64, len: usize) -> Vec<i32> {
+        let mut rng = SplitMix64::new(seed);
+        (0..len).map(|_| rng.next_u64() as i32).collect()
+    }
+
+    #[test]
+    fn test_sort_i32x8() {
+        let v = i32x8::from_array([5, -3, 8, 8, 0, i32::MIN, 7, 1]);
+        assert_eq!(sort_i32x8(v).to_array(), [i32::MIN, -3, 0, 1, 5, 7, 8, 8]);
+    }
+
+    #[test]
+    fn test_sort_i32() {
+        for (seed, len) in [0, 1, 7, 63, 64, 65, 100, 1000, 1024, 4099]
+            .into_iter()
+            .enumerate()
+        {
+            let mut values = random_values(seed as u64, len);
+            let mut expected = values.clone();
+            expected.sort_unstable();
+
+            sort_i32(&mut values);
+            assert_eq!(values, expected, "len {len}");
+        }
+
+        // Lots of duplicates and values equal to the padding
+        let mut values = (0..1000)
+            .map(|i| [i32::MAX, 3, -1][i % 3])
+            .collect::<Vec<_>>();
+        let mut expected = values.clone();
+        expected.sort_unstable();
+
+        sort_i32(&mut values);
+        assert_eq!(values, expected);
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_sort_unstable_1k(b: &mut Bencher) {
+        let values = random_values(1, 1000);
+        b.iter(|| test::black_box(values.clone()).sort_unstable());
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_simd_sort_1k(b: &mut Bencher) {
+        let values = random_values(1, 1000);
+        b.iter(|| sort_i32(&mut test::black_box(values.clone())));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_sort_unstable_100k(b: &mut Bencher) {
+        let values = random_values(1, 100_000);
+        b.iter(|| test::black_box(values.clone()).sort_unstable());
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_simd_sort_100k(b: &mut Bencher) {
+        let values = random_values(1, 100_000);
+        b.iter(|| sort_i32(&mut test::black_box(values.clone())));
+    }
+}
@@ -0,0 +1,164 @@
+//! Solve day 1 straight from a reader, a buffer at a time, so a huge input
+//! only ever exists in memory as the two columns.
+
+use std::io::{self, ErrorKind, Read};
+
+use crate::{pipeline::Reduce, try_parse_lines_into, LINE_LENGTH};
+
+/// Default read size. Any size works as long as it holds a whole line.
+pub const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Parse both columns from `reader`, checking the layout like
+/// `try_parse_columns`. Layout errors come back as `ErrorKind::InvalidData`
+/// wrapping a `ParseError`.
+pub fn parse_columns(reader: impl Read) -> io::Result<(Vec<i32>, Vec<i32>)> {
+    parse_columns_with_buffer(reader, &mut vec![0; BUFFER_SIZE])
+}
+
+/// `parse_columns` reading into `buffer`, which must hold at least one line
+pub fn parse_columns_with_buffer(
+    mut reader: impl Read,
+    buffer: &mut [u8],
+) -> io::Result<(Vec<i32>, Vec<i32>)> {
+    assert!(buffer.len() >= LINE_LENGTH, "buffer must hold a whole line");
+
+    let mut left_column = Vec::new();
+    let mut right_column = Vec::new();
+
+    let mut filled = 0;
+    let mut line = 0;
+
+    loop {
+        let read = match reader.read(&mut buffer[filled..]) {
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        filled += read;
+        let end_of_input = read == 0;
+
+        // Only whole lines get parsed, a line split across reads waits at the
+        // front of the buffer for the rest of it. At the end whatever is left
+        // is the last line, which may be missing its newline.
+        let complete = if end_of_input {
+            filled
+        } else {
+            filled - filled % LINE_LENGTH
+        };
+
+        try_parse_lines_into(
+            &buffer[..complete],
+            line,
+            &mut left_column,
+            &mut right_column,
+        )
+        .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+
+        line += complete / LINE_LENGTH;
+        buffer.copy_within(complete..filled, 0);
+        filled -= complete;
+
+        if end_of_input {
+            return Ok((left_column, right_column));
+        }
+    }
+}
+
+/// Part 1 over everything `reader` produces
+pub fn distance<R: Reduce<i32>>(reader: impl Read) -> io::Result<R::Output> {
+    let (mut left_column, mut right_column) = parse_columns(reader)?;
+
+    left_column.sort_unstable();
+    right_column.sort_unstable();
+
+    Ok(R::reduce(&left_column, &right_column))
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "bench")]
+    extern crate test;
+    use super::*;
+    use crate::{
+        answers::expected,
+        generate::{generate, ListOptions},
+        pipeline::Fold,
+        try_parse_columns, ParseError, INPUT,
+    };
+    #[cfg(feature = "bench")]
+    use test::Bencher;
+
+    /// Hands out at most `max` bytes per read, to split lines in awkward places
+    struct Trickle<'a> {
+        bytes: &'a [u8],
+        max: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.max.min(buf.len()).min(self.bytes.len());
+            buf[..n].copy_from_slice(&self.bytes[..n]);
+            self.bytes = &self.bytes[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance::<Fold>(INPUT).unwrap(), expected(INPUT, "part_1"));
+    }
+
+    #[test]
+    fn test_split_records() {
+        let list = generate(1, &ListOptions::new(500));
+        let expected = try_parse_columns(&list.bytes).unwrap();
+
+        for buffer_size in [LINE_LENGTH, LINE_LENGTH + 1, 20, 27, 28, 100, 4096] {
+            for max in [1, 5, 13, 14, 15, 1000] {
+                let reader = Trickle {
+                    bytes: &list.bytes,
+                    max,
+                };
+                let parsed = parse_columns_with_buffer(reader, &mut vec![0; buffer_size]).unwrap();
+                assert_eq!(parsed, expected, "buffer {buffer_size}, reads of {max}");
+            }
+        }
+
+        // No newline on the last line
+        let reader = Trickle {
+            bytes: INPUT,
+            max: 9,
+        };
+        let parsed = parse_columns_with_buffer(reader, &mut [0; 20]).unwrap();
+        assert_eq!(parsed, try_parse_columns(INPUT).unwrap());
+    }
+
+    #[test]
+    fn test_errors_point_into_whole_input() {
+        let mut bytes = generate(2, &ListOptions::new(100)).bytes;
+        bytes[50 * LINE_LENGTH + 6] = b'x';
+
+        let e = parse_columns_with_buffer(&bytes[..], &mut [0; 30]).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            e.into_inner().unwrap().downcast::<ParseError>().unwrap(),
+            Box::new(ParseError::BadSeparator {
+                offset: 50 * LINE_LENGTH + 5
+            })
+        );
+
+        let truncated = &INPUT[..INPUT.len() - 3];
+        let e = parse_columns(truncated).unwrap_err();
+        assert_eq!(
+            e.into_inner().unwrap().downcast::<ParseError>().unwrap(),
+            Box::new(ParseError::TruncatedLine { line: 999 })
+        );
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_stream_generated_100k(b: &mut Bencher) {
+        let list = generate(1, &ListOptions::new(100_000));
+        b.iter(|| distance::<Fold>(test::black_box(&list.bytes[..])).unwrap());
+    }
+}
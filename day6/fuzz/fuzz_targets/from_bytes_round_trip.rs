@@ -0,0 +1,64 @@
+#![no_main]
+
+use day6::{
+    point::Point,
+    sparse::{SparseGrid, MAX_OBSTICLES_PER_LINE},
+};
+use libfuzzer_sys::fuzz_target;
+
+// Arbitrary bytes are almost never a valid map, so build one from them instead.
+// The first two bytes pick the size, the rest pick cells for obsticles and the
+// guard. This gets the SIMD scan running over every width and chunk alignment.
+fuzz_target!(|data: &[u8]| {
+    let [w, h, start, cells @ ..] = data else {
+        return;
+    };
+
+    let width = *w as usize + 1;
+    let height = *h as usize + 1;
+
+    let mut map = vec![b'.'; width * height];
+    let mut per_row = vec![0; height];
+    let mut per_column = vec![0; width];
+
+    for pair in cells.chunks_exact(2) {
+        let i = u16::from_le_bytes([pair[0], pair[1]]) as usize % map.len();
+        let (x, y) = (i % width, i / width);
+        if per_row[y] < MAX_OBSTICLES_PER_LINE && per_column[x] < MAX_OBSTICLES_PER_LINE {
+            map[i] = b'#';
+            per_row[y] += 1;
+            per_column[x] += 1;
+        }
+    }
+
+    let start = *start as usize * map.len() / 256;
+    map[start] = b'^';
+
+    let mut obsticles = Vec::new();
+    let mut input = Vec::with_capacity((width + 1) * height);
+    for (row, y) in map.chunks(width).zip(0_u32..) {
+        for (&c, x) in row.iter().zip(0_u32..) {
+            if c == b'#' {
+                obsticles.push(Point::new(x, y));
+            }
+        }
+        input.extend_from_slice(row);
+        input.push(b'\n');
+    }
+
+    let grid = SparseGrid::from_bytes(&input);
+
+    assert_eq!(grid.width, width as u32);
+    assert_eq!(grid.height, height as u32);
+    assert_eq!(
+        grid.start_point,
+        Point::new((start % width) as u32, (start / width) as u32)
+    );
+    assert_eq!(grid.obsticles(), obsticles);
+
+    // Both tables have to agree
+    for p in &obsticles {
+        assert!(grid.x_to_y.contains(p.x, p.y));
+        assert!(grid.y_to_x.contains(p.y, p.x));
+    }
+});
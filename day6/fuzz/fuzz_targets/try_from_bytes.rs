@@ -0,0 +1,31 @@
+#![no_main]
+
+use day6::{point::Point, sparse::SparseGrid};
+use libfuzzer_sys::fuzz_target;
+
+// Arbitrary bytes must either be rejected or parse to exactly what a naive
+// scalar parse finds
+fuzz_target!(|data: &[u8]| {
+    let Ok(grid) = SparseGrid::try_from_bytes(data) else {
+        return;
+    };
+
+    let rows = data[..data.len() - 1].split(|c| *c == b'\n').collect::<Vec<_>>();
+
+    let mut obsticles = Vec::new();
+    let mut start = None;
+    for (row, y) in rows.iter().zip(0_u32..) {
+        for (&c, x) in row.iter().zip(0_u32..) {
+            match c {
+                b'#' => obsticles.push(Point::new(x, y)),
+                b'^' => start = Some(Point::new(x, y)),
+                _ => {}
+            }
+        }
+    }
+
+    assert_eq!(grid.width, rows[0].len() as u32);
+    assert_eq!(grid.height, rows.len() as u32);
+    assert_eq!(Some(grid.start_point), start);
+    assert_eq!(grid.obsticles(), obsticles);
+});
@@ -0,0 +1,71 @@
+use std::{env, process, str::FromStr};
+
+use day6::{
+    generate::{generate, GuardOutcome, MapOptions},
+    sparse::MAX_SIZE,
+};
+
+const USAGE: &str =
+    "usage: generate_map <width> <height> <density> <seed> <output> [any|exits|loops] [max per line]";
+
+fn parse_or_exit<T: FromStr>(arg: &str, name: &str) -> T {
+    arg.parse().unwrap_or_else(|_| {
+        eprintln!("invalid {name}: {arg}\n{USAGE}");
+        process::exit(1);
+    })
+}
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    if args.len() < 5 {
+        eprintln!("{USAGE}");
+        process::exit(1);
+    }
+
+    let width: u32 = parse_or_exit(&args[0], "width");
+    let height: u32 = parse_or_exit(&args[1], "height");
+    let density: f64 = parse_or_exit(&args[2], "density");
+    let seed: u64 = parse_or_exit(&args[3], "seed");
+    let output = &args[4];
+
+    if width == 0 || height == 0 {
+        eprintln!("the map needs at least one cell\n{USAGE}");
+        process::exit(1);
+    }
+    if !(0.0..=1.0).contains(&density) {
+        eprintln!("invalid density: {density}, must be between 0 and 1\n{USAGE}");
+        process::exit(1);
+    }
+    if width > MAX_SIZE || height > MAX_SIZE {
+        eprintln!(
+            "note: SparseGrid only loads maps up to {MAX_SIZE}x{MAX_SIZE}, only the dense solvers can read this one"
+        );
+    }
+
+    let outcome = match args.get(5).map(String::as_str) {
+        None | Some("any") => GuardOutcome::Any,
+        Some("exits") => GuardOutcome::Exits,
+        Some("loops") => GuardOutcome::Loops,
+        Some(other) => {
+            eprintln!("invalid outcome: {other}\n{USAGE}");
+            process::exit(1);
+        }
+    };
+
+    let max_obsticles_per_line = args.get(6).map(|arg| parse_or_exit(arg, "max per line"));
+
+    let map = generate(
+        seed,
+        &MapOptions {
+            outcome,
+            max_obsticles_per_line,
+            ..MapOptions::new(width, height, density)
+        },
+    );
+
+    if let Err(e) = map.write_to(output) {
+        eprintln!("failed to write {output}: {e}");
+        process::exit(1);
+    }
+}
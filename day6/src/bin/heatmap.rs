@@ -0,0 +1,50 @@
+use std::{
+    env, fs,
+    io::{self, BufWriter, Write},
+    process,
+};
+
+use day6::sparse::SparseGrid;
+
+const USAGE: &str = "usage: heatmap <input> [pgm|ppm|csv|directions]";
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    if args.is_empty() || args.len() > 2 {
+        eprintln!("{USAGE}");
+        process::exit(1);
+    }
+
+    let input = fs::read(&args[0]).unwrap_or_else(|e| {
+        eprintln!("failed to read {}: {e}", args[0]);
+        process::exit(1);
+    });
+
+    let grid = SparseGrid::try_from_bytes(&input).unwrap_or_else(|e| {
+        eprintln!("invalid input: {e}");
+        process::exit(1);
+    });
+
+    let heatmap = grid.heatmap();
+    if heatmap.looped {
+        eprintln!("the guard goes round a loop, counts stop where it repeats");
+    }
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    let written = match args.get(1).map_or("ppm", String::as_str) {
+        "pgm" => heatmap.write_pgm(&mut out),
+        "ppm" => heatmap.write_ppm(&mut out),
+        "csv" => heatmap.write_csv(&mut out),
+        "directions" => heatmap.write_directions_csv(&mut out),
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(1);
+        }
+    };
+
+    if let Err(e) = written.and_then(|_| out.flush()) {
+        eprintln!("failed to write: {e}");
+        process::exit(1);
+    }
+}
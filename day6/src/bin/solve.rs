@@ -0,0 +1,55 @@
+use std::{env, fmt::Display, fs, process};
+
+use day6::{
+    answers::{self, hash, Check},
+    sparse::{LoopDetection, SparseGrid},
+};
+
+const USAGE: &str = "usage: solve <input>";
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    if args.len() != 1 {
+        eprintln!("{USAGE}");
+        process::exit(1);
+    }
+
+    let input = fs::read(&args[0]).unwrap_or_else(|e| {
+        eprintln!("failed to read {}: {e}", args[0]);
+        process::exit(1);
+    });
+
+    let mut grid = SparseGrid::try_from_bytes(&input).unwrap_or_else(|e| {
+        eprintln!("invalid input: {e}");
+        process::exit(1);
+    });
+
+    let answers = answers::known();
+    let mut wrong = false;
+    let mut report = |part: &str, answer: &dyn Display| {
+        let note = match answers.check(&input, part, answer) {
+            Check::Correct => String::new(),
+            Check::Wrong { expected } => {
+                wrong = true;
+                format!(" (expected {expected})")
+            }
+            Check::Unknown => " (unknown)".to_string(),
+        };
+        println!("{part}: {answer}{note}");
+    };
+
+    report("start", &grid.start_point);
+    report("part_1", &grid.part_1());
+    report("part_2", &grid.part_2(LoopDetection::TurnGraph));
+
+    if answers.get(&input, "part_1").is_none() {
+        eprintln!(
+            "input {:016x} isn't in answers.txt, add its answers to check them",
+            hash(&input)
+        );
+    }
+    if wrong {
+        process::exit(1);
+    }
+}
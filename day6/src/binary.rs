@@ -0,0 +1,416 @@
+//! Compact binary form of a parsed `SparseGrid`, so a map only has to be
+//! scanned once and can be cached on disk.
+//!
+//! Everything is little endian:
+//!
+//! ```text
+//! magic    b"D6SG"
+//! version  u8
+//! width    u32
+//! height   u32
+//! start    u32 x, u32 y
+//! x_to_y   u32 entry count, then per entry a u8 count and that many u16s
+//! y_to_x   the same
+//! ```
+//!
+//! Entries keep their insertion order, so a loaded grid behaves exactly like
+//! the one that was saved.
+
+use std::{fs, io, path::Path};
+
+use crate::{
+    point::Point,
+    sparse::{CoordinateLookupTable, SparseGrid, MAX_OBSTICLES_PER_LINE, MAX_SIZE},
+};
+
+pub const MAGIC: &[u8; 4] = b"D6SG";
+
+/// Bumped whenever the layout changes
+pub const VERSION: u8 = 2;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LoadError {
+    BadMagic,
+    UnsupportedVersion(u8),
+    /// Ran out of bytes partway through
+    Truncated,
+    TrailingBytes,
+    TooBig {
+        width: u32,
+        height: u32,
+    },
+    StartOutOfBounds,
+    TooManyObsticles,
+    /// An obsticle is in one lookup table but not the other, or off the grid,
+    /// or a table doesn't have an entry for every line
+    Inconsistent,
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::BadMagic => write!(f, "not a saved grid"),
+            LoadError::UnsupportedVersion(version) => {
+                write!(f, "version {version} isn't supported, only {VERSION} is")
+            }
+            LoadError::Truncated => write!(f, "unexpected end of data"),
+            LoadError::TrailingBytes => write!(f, "trailing bytes after the grid"),
+            LoadError::TooBig { width, height } => write!(
+                f,
+                "grid is {width}x{height}, at most {MAX_SIZE}x{MAX_SIZE} is supported"
+            ),
+            LoadError::StartOutOfBounds => write!(f, "the start is off the grid"),
+            LoadError::TooManyObsticles => {
+                write!(f, "a line has more than {MAX_OBSTICLES_PER_LINE} obsticles")
+            }
+            LoadError::Inconsistent => write!(f, "the lookup tables disagree"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], LoadError> {
+        if self.0.len() < n {
+            return Err(LoadError::Truncated);
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, LoadError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, LoadError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// A table with an entry for each of the `size` rows or columns of the
+    /// grid, the way `from_bytes` and `resize` keep them
+    fn table(&mut self, size: u32) -> Result<CoordinateLookupTable, LoadError> {
+        if self.u32()? != size {
+            return Err(LoadError::Inconsistent);
+        }
+
+        let mut table = CoordinateLookupTable::with_size(size);
+        for first in 0..size {
+            let count = self.u8()? as usize;
+            if count > MAX_OBSTICLES_PER_LINE {
+                return Err(LoadError::TooManyObsticles);
+            }
+            for second in self.take(count * 2)?.chunks(2) {
+                table.insert(first, u16::from_le_bytes([second[0], second[1]]) as u32);
+            }
+        }
+
+        Ok(table)
+    }
+}
+
+fn write_table(bytes: &mut Vec<u8>, table: &CoordinateLookupTable) {
+    bytes.extend_from_slice(&(table.iter().count() as u32).to_le_bytes());
+    for entry in table.iter() {
+        bytes.push(entry.as_slice().len() as u8);
+        for second in entry.as_slice() {
+            bytes.extend_from_slice(&second.to_le_bytes());
+        }
+    }
+}
+
+/// Both tables hold the same obsticles, each of them once and on the grid
+fn consistent(grid: &SparseGrid) -> bool {
+    let (width, height) = (grid.width as usize, grid.height as usize);
+    let mut seen = vec![0_u64; (width * height).div_ceil(64)];
+    let mut unmatched = 0;
+
+    for (entry, x) in grid.x_to_y.iter().zip(0_usize..) {
+        for &y in entry.as_slice() {
+            let bit = y as usize * width + x;
+            if x >= width || y as usize >= height || seen[bit / 64] & 1 << (bit % 64) != 0 {
+                return false;
+            }
+            seen[bit / 64] |= 1 << (bit % 64);
+            unmatched += 1;
+        }
+    }
+
+    for (entry, y) in grid.y_to_x.iter().zip(0_usize..) {
+        for &x in entry.as_slice() {
+            let bit = y * width + x as usize;
+            if x as usize >= width || y >= height || seen[bit / 64] & 1 << (bit % 64) == 0 {
+                return false;
+            }
+            seen[bit / 64] &= !(1 << (bit % 64));
+            unmatched -= 1;
+        }
+    }
+
+    unmatched == 0
+}
+
+impl SparseGrid {
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+
+        for value in [
+            self.width,
+            self.height,
+            self.start_point.x,
+            self.start_point.y,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        write_table(&mut bytes, &self.x_to_y);
+        write_table(&mut bytes, &self.y_to_x);
+        bytes
+    }
+
+    /// Load what `to_binary` wrote, checking it describes a grid we can hold
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, LoadError> {
+        let mut reader = Reader(bytes);
+
+        if reader.take(MAGIC.len()).map_err(|_| LoadError::BadMagic)? != MAGIC {
+            return Err(LoadError::BadMagic);
+        }
+        let version = reader.u8()?;
+        if version != VERSION {
+            return Err(LoadError::UnsupportedVersion(version));
+        }
+
+        let width = reader.u32()?;
+        let height = reader.u32()?;
+        if width > MAX_SIZE || height > MAX_SIZE {
+            return Err(LoadError::TooBig { width, height });
+        }
+
+        let start_point = Point::new(reader.u32()?, reader.u32()?);
+        if start_point.x >= width || start_point.y >= height {
+            return Err(LoadError::StartOutOfBounds);
+        }
+
+        let x_to_y = reader.table(width)?;
+        let y_to_x = reader.table(height)?;
+        if !reader.0.is_empty() {
+            return Err(LoadError::TrailingBytes);
+        }
+
+        let grid = Self {
+            width,
+            height,
+            start_point,
+            x_to_y,
+            y_to_x,
+        };
+        if !consistent(&grid) {
+            return Err(LoadError::Inconsistent);
+        }
+
+        Ok(grid)
+    }
+
+    pub fn write_binary_to(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_binary())
+    }
+
+    /// `from_binary` on a file, with load errors as `InvalidData`
+    pub fn read_binary_from(path: impl AsRef<Path>) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        Self::from_binary(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "bench")]
+    extern crate test;
+    use super::*;
+    use crate::{
+        answers::expected,
+        generate::{generate, MapOptions},
+        sparse::LoopDetection,
+    };
+    #[cfg(feature = "bench")]
+    use test::Bencher;
+
+    const INPUT: &[u8] = include_bytes!("input.txt");
+
+    fn tables(table: &CoordinateLookupTable) -> Vec<Vec<u16>> {
+        table
+            .iter()
+            .map(|entry| entry.as_slice().to_vec())
+            .collect()
+    }
+
+    fn assert_same(a: &SparseGrid, b: &SparseGrid) {
+        assert_eq!(
+            (a.width, a.height, a.start_point),
+            (b.width, b.height, b.start_point)
+        );
+        assert_eq!(tables(&a.x_to_y), tables(&b.x_to_y));
+        assert_eq!(tables(&a.y_to_x), tables(&b.y_to_x));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let grid = SparseGrid::from_bytes(INPUT);
+        let mut loaded = SparseGrid::from_binary(&grid.to_binary()).unwrap();
+
+        assert_same(&grid, &loaded);
+        assert_eq!(loaded.part_1(), expected(INPUT, "part_1"));
+        assert_eq!(
+            loaded.part_2(LoopDetection::TurnGraph),
+            expected(INPUT, "part_2")
+        );
+
+        for seed in 0..20 {
+            let size = 1 + seed as u32 * 7;
+            let map = generate(
+                seed,
+                &MapOptions {
+                    max_obsticles_per_line: Some(MAX_OBSTICLES_PER_LINE as u32),
+                    ..MapOptions::new(size, size / 2 + 1, 0.15)
+                },
+            );
+            let grid = SparseGrid::try_from_bytes(&map.bytes).unwrap();
+            assert_same(&grid, &SparseGrid::from_binary(&grid.to_binary()).unwrap());
+        }
+
+        // Past what a u8 coordinate could hold
+        let map = generate(
+            1,
+            &MapOptions {
+                max_obsticles_per_line: Some(MAX_OBSTICLES_PER_LINE as u32),
+                ..MapOptions::new(1000, 300, 0.01)
+            },
+        );
+        let grid = SparseGrid::try_from_bytes(&map.bytes).unwrap();
+        assert_same(&grid, &SparseGrid::from_binary(&grid.to_binary()).unwrap());
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("day6-grid-{}.bin", std::process::id()));
+        let grid = SparseGrid::from_bytes(INPUT);
+
+        grid.write_binary_to(&path).unwrap();
+        let loaded = SparseGrid::read_binary_from(&path);
+        fs::write(&path, b"nope").unwrap();
+        let invalid = SparseGrid::read_binary_from(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_same(&grid, &loaded.unwrap());
+        assert_eq!(invalid.err().unwrap().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let bytes = SparseGrid::from_bytes(b"..#\n#^.\n").to_binary();
+
+        for len in 0..bytes.len() {
+            let expected = if len < MAGIC.len() {
+                LoadError::BadMagic
+            } else {
+                LoadError::Truncated
+            };
+            assert_eq!(
+                SparseGrid::from_binary(&bytes[..len]).err(),
+                Some(expected),
+                "{len} bytes"
+            );
+        }
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            SparseGrid::from_binary(&trailing).err(),
+            Some(LoadError::TrailingBytes)
+        );
+
+        let mut magic = bytes.clone();
+        magic[0] = b'X';
+        assert_eq!(
+            SparseGrid::from_binary(&magic).err(),
+            Some(LoadError::BadMagic)
+        );
+
+        let mut version = bytes.clone();
+        version[4] = VERSION + 1;
+        assert_eq!(
+            SparseGrid::from_binary(&version).err(),
+            Some(LoadError::UnsupportedVersion(VERSION + 1))
+        );
+
+        let mut too_big = bytes.clone();
+        too_big[5..9].copy_from_slice(&100_000_u32.to_le_bytes());
+        assert_eq!(
+            SparseGrid::from_binary(&too_big).err(),
+            Some(LoadError::TooBig {
+                width: 100_000,
+                height: 2
+            })
+        );
+
+        let mut start = bytes.clone();
+        start[13..17].copy_from_slice(&3_u32.to_le_bytes());
+        assert_eq!(
+            SparseGrid::from_binary(&start).err(),
+            Some(LoadError::StartOutOfBounds)
+        );
+
+        // x_to_y has 3 entries of [1], [], [0], move the first obsticle to y 0
+        let mut inconsistent = bytes.clone();
+        assert_eq!(&inconsistent[25..28], &[1, 1, 0]);
+        inconsistent[26] = 0;
+        assert_eq!(
+            SparseGrid::from_binary(&inconsistent).err(),
+            Some(LoadError::Inconsistent)
+        );
+    }
+
+    #[test]
+    fn test_load_short_table() {
+        // x_to_y is [0], [], [] and y_to_x is [0], []. Drop the last, empty, column
+        let bytes = SparseGrid::from_bytes(b"#..\n.^.\n").to_binary();
+        assert_eq!(&bytes[21..30], &[3, 0, 0, 0, 1, 0, 0, 0, 0]);
+
+        let short = [&bytes[..21], &[2, 0, 0, 0, 1, 0, 0, 0], &bytes[30..]].concat();
+        assert_eq!(
+            SparseGrid::from_binary(&short).err(),
+            Some(LoadError::Inconsistent)
+        );
+    }
+
+    #[test]
+    fn test_load_full_line() {
+        let row = [vec![b'#'; MAX_OBSTICLES_PER_LINE], vec![b'.'; 7]].concat();
+        let input = [&row[..], b"\n", &[b'.'; 29], b"^\n"].concat();
+        let mut grid = SparseGrid::try_from_bytes(&input).unwrap();
+        let mut loaded = SparseGrid::from_binary(&grid.to_binary()).unwrap();
+        assert_eq!(loaded.part_2(LoopDetection::TurnGraph), 0);
+
+        // One more than a map can hold, which only gets in by going round the
+        // edits' checks
+        grid.y_to_x.insert(0, MAX_OBSTICLES_PER_LINE as u32);
+        grid.x_to_y.insert(MAX_OBSTICLES_PER_LINE as u32, 0);
+        assert_eq!(
+            SparseGrid::from_binary(&grid.to_binary()).err(),
+            Some(LoadError::TooManyObsticles)
+        );
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_from_binary(b: &mut Bencher) {
+        let bytes = SparseGrid::from_bytes(INPUT).to_binary();
+        b.iter(|| SparseGrid::from_binary(test::black_box(&bytes)).unwrap());
+    }
+}
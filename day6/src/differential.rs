@@ -0,0 +1,191 @@
+//! Differential tests between every solver on randomly generated maps.
+//!
+//! When the solvers disagree the map is shrunk, by dropping rows, columns and
+//! obsticles while they still disagree, and the smallest map is printed.
+
+use std::panic;
+
+use crate::{
+    find_extents, find_start,
+    generate::{generate, MapOptions},
+    is_loop, part_1, part_2, part_2_obsticles,
+    point::Point,
+    sparse::{LoopDetection, SparseGrid, MAX_OBSTICLES_PER_LINE},
+};
+
+fn to_bytes(rows: &[Vec<u8>]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for row in rows {
+        bytes.extend_from_slice(row);
+        bytes.push(b'\n');
+    }
+    bytes
+}
+
+fn sorted(mut points: Vec<Point>) -> Vec<Point> {
+    points.sort_unstable_by_key(|p| (p.y, p.x));
+    points
+}
+
+/// The property: every solver gives the same answer without panicking. Returns
+/// what went wrong if they don't.
+fn check(input: &[u8]) -> Result<(), String> {
+    panic::catch_unwind(|| solvers_agree(input)).unwrap_or_else(|e| {
+        let message = e
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| e.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(format!("panicked: {message}"))
+    })
+}
+
+fn solvers_agree(input: &[u8]) -> Result<(), String> {
+    let extents = find_extents(input);
+    let start = find_start(input, extents.width).expect("map has a guard");
+    let grid = input.split(|c| *c == b'\n').collect::<Vec<_>>();
+
+    // Real inputs always let the guard out, none of the solvers handle a guard
+    // that loops without any help
+    if is_loop(&grid, start, extents, Point::new(u32::MAX, u32::MAX)) {
+        return Ok(());
+    }
+
+    let dense = part_1(&grid, start, extents) as u32;
+    let sparse = SparseGrid::from_bytes(input).part_1();
+    if dense != sparse {
+        return Err(format!("part 1: dense {dense} != sparse {sparse}"));
+    }
+
+    let dense = part_2_obsticles(&grid, start, extents);
+    let count = part_2(&grid, start, extents);
+    if count != dense.len() {
+        return Err(format!(
+            "part 2: dense {count} != brute force {}",
+            dense.len()
+        ));
+    }
+
+    for loop_detection in [
+        LoopDetection::HashSet,
+        LoopDetection::Brent,
+        LoopDetection::TurnGraph,
+    ] {
+        let sparse = sorted(SparseGrid::from_bytes(input).part_2_obsticles(loop_detection));
+        if dense != sparse {
+            return Err(format!(
+                "part 2 ({loop_detection:?}): dense {dense:?} != sparse {sparse:?}"
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Greedily drop rows, columns and obsticles while the map still `fails`.
+/// Never removes the guard.
+fn shrink(mut rows: Vec<Vec<u8>>, fails: impl Fn(&[Vec<u8>]) -> bool) -> Vec<Vec<u8>> {
+    loop {
+        let mut shrunk = false;
+
+        let mut y = 0;
+        while y < rows.len() {
+            if rows.len() > 1 && !rows[y].contains(&b'^') {
+                let mut candidate = rows.clone();
+                candidate.remove(y);
+                if fails(&candidate) {
+                    rows = candidate;
+                    shrunk = true;
+                    continue;
+                }
+            }
+            y += 1;
+        }
+
+        let mut x = 0;
+        while x < rows[0].len() {
+            if rows[0].len() > 1 && rows.iter().all(|row| row[x] != b'^') {
+                let mut candidate = rows.clone();
+                for row in candidate.iter_mut() {
+                    row.remove(x);
+                }
+                if fails(&candidate) {
+                    rows = candidate;
+                    shrunk = true;
+                    continue;
+                }
+            }
+            x += 1;
+        }
+
+        for y in 0..rows.len() {
+            for x in 0..rows[y].len() {
+                if rows[y][x] == b'#' {
+                    let mut candidate = rows.clone();
+                    candidate[y][x] = b'.';
+                    if fails(&candidate) {
+                        rows = candidate;
+                        shrunk = true;
+                    }
+                }
+            }
+        }
+
+        if !shrunk {
+            return rows;
+        }
+    }
+}
+
+fn assert_solvers_agree(seeds: std::ops::Range<u64>, options: impl Fn(u64) -> MapOptions) {
+    for seed in seeds {
+        let map = generate(seed, &options(seed));
+
+        if let Err(e) = check(&map.bytes) {
+            let rows = map.rows().map(<[u8]>::to_vec).collect::<Vec<_>>();
+            let minimal = to_bytes(&shrink(rows, |rows| check(&to_bytes(rows)).is_err()));
+            let reason = check(&minimal).unwrap_err();
+
+            panic!(
+                "solvers disagree on seed {seed}: {e}\nminimal map ({reason}):\n{}",
+                String::from_utf8_lossy(&minimal)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_solvers_agree_small() {
+    assert_solvers_agree(0..300, |seed| {
+        let size = 3 + (seed % 10) as u32;
+        MapOptions {
+            max_obsticles_per_line: Some(MAX_OBSTICLES_PER_LINE as u32),
+            ..MapOptions::new(size, size + (seed % 3) as u32, 0.2)
+        }
+    });
+}
+
+#[test]
+fn test_solvers_agree_medium() {
+    assert_solvers_agree(0..40, |seed| MapOptions {
+        max_obsticles_per_line: Some(MAX_OBSTICLES_PER_LINE as u32),
+        ..MapOptions::new(40, 30, 0.05 + (seed % 4) as f64 * 0.05)
+    });
+}
+
+#[test]
+fn test_shrink() {
+    // Fails whenever there's an obsticle to the right of the guard, so should
+    // shrink down to just the guard and that obsticle
+    let rows = ["..#.#", "...#.", "#.^.#", "#...."]
+        .iter()
+        .map(|row| row.as_bytes().to_vec())
+        .collect::<Vec<_>>();
+
+    let minimal = shrink(rows, |rows| {
+        rows.iter()
+            .any(|row| row.iter().skip_while(|c| **c != b'^').any(|c| *c == b'#'))
+    });
+
+    assert_eq!(to_bytes(&minimal), b"^#\n");
+}
@@ -16,6 +16,20 @@ impl Direction {
         }
     }
 
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Right => Direction::Left,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+        }
+    }
+
+    /// Dense 0..4 index, handy for packing a direction into an array index
+    pub fn index(&self) -> usize {
+        (*self as u8).trailing_zeros() as usize
+    }
+
     pub fn potential_blocker_count(byte: u8) -> u8 {
         let mut sum = 0;
 
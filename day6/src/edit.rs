@@ -0,0 +1,329 @@
+//! Editing a `SparseGrid` in place, for trying out changes to a map without
+//! going back through the text. Every edit keeps `x_to_y` and `y_to_x` in step.
+
+use crate::{
+    point::Point,
+    sparse::{SmallVec, SparseGrid, MAX_OBSTICLES_PER_LINE, MAX_SIZE},
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EditError {
+    OutOfBounds(Point),
+    /// Obsticles and the guard can't share a cell
+    Occupied(Point),
+    TooManyObsticlesInRow {
+        row: u32,
+    },
+    TooManyObsticlesInColumn {
+        column: u32,
+    },
+    TooBig {
+        width: u32,
+        height: u32,
+    },
+    /// Shrinking would leave the guard off the map
+    StartOutOfBounds,
+}
+
+impl std::fmt::Display for EditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EditError::OutOfBounds(p) => write!(f, "{p} is off the map"),
+            EditError::Occupied(p) => write!(f, "{p} is already taken"),
+            EditError::TooManyObsticlesInRow { row } => write!(
+                f,
+                "row {row} already has {MAX_OBSTICLES_PER_LINE} obsticles"
+            ),
+            EditError::TooManyObsticlesInColumn { column } => write!(
+                f,
+                "column {column} already has {MAX_OBSTICLES_PER_LINE} obsticles"
+            ),
+            EditError::TooBig { width, height } => write!(
+                f,
+                "map would be {width}x{height}, at most {MAX_SIZE}x{MAX_SIZE} is supported"
+            ),
+            EditError::StartOutOfBounds => write!(f, "the guard would be off the map"),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+impl SparseGrid {
+    fn check_in_bounds(&self, p: Point) -> Result<(), EditError> {
+        if p.x < self.width && p.y < self.height {
+            Ok(())
+        } else {
+            Err(EditError::OutOfBounds(p))
+        }
+    }
+
+    pub fn is_obsticle(&self, p: Point) -> bool {
+        self.y_to_x.contains(p.y, p.x)
+    }
+
+    /// Put an obsticle at `p`. False if there already was one.
+    pub fn add_obsticle(&mut self, p: Point) -> Result<bool, EditError> {
+        self.check_in_bounds(p)?;
+        if p == self.start_point {
+            return Err(EditError::Occupied(p));
+        }
+        if self.is_obsticle(p) {
+            return Ok(false);
+        }
+
+        let full = |line: Option<&SmallVec>| {
+            line.is_some_and(|line| line.as_slice().len() >= MAX_OBSTICLES_PER_LINE)
+        };
+        if full(self.y_to_x.get(p.y)) {
+            return Err(EditError::TooManyObsticlesInRow { row: p.y });
+        }
+        if full(self.x_to_y.get(p.x)) {
+            return Err(EditError::TooManyObsticlesInColumn { column: p.x });
+        }
+
+        self.y_to_x.insert(p.y, p.x);
+        self.x_to_y.insert(p.x, p.y);
+        Ok(true)
+    }
+
+    /// Clear the obsticle at `p`. False if there wasn't one.
+    pub fn remove_obsticle(&mut self, p: Point) -> bool {
+        let removed = self.y_to_x.remove(p.y, p.x);
+        self.x_to_y.remove(p.x, p.y);
+        removed
+    }
+
+    /// Move the guard to `p`, still facing up
+    pub fn set_start(&mut self, p: Point) -> Result<(), EditError> {
+        self.check_in_bounds(p)?;
+        if self.is_obsticle(p) {
+            return Err(EditError::Occupied(p));
+        }
+
+        self.start_point = p;
+        Ok(())
+    }
+
+    /// Grow or shrink the map from the bottom right. New cells are empty and
+    /// obsticles that end up off the map are dropped. Nothing changes on an error.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), EditError> {
+        if width > MAX_SIZE || height > MAX_SIZE {
+            return Err(EditError::TooBig { width, height });
+        }
+        if self.start_point.x >= width || self.start_point.y >= height {
+            return Err(EditError::StartOutOfBounds);
+        }
+
+        self.x_to_y.resize(width);
+        self.y_to_x.resize(height);
+        for column in self.x_to_y.iter_mut() {
+            column.retain(|y| (y as u32) < height);
+        }
+        for row in self.y_to_x.iter_mut() {
+            row.retain(|x| (x as u32) < width);
+        }
+
+        self.width = width;
+        self.height = height;
+        Ok(())
+    }
+
+    /// The map as text, the way `from_bytes` reads it
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let row_length = self.width as usize + 1;
+        let mut bytes = vec![b'.'; row_length * self.height as usize];
+
+        for y in 0..self.height as usize {
+            bytes[y * row_length + self.width as usize] = b'\n';
+        }
+        for p in self.obsticles() {
+            bytes[p.y as usize * row_length + p.x as usize] = b'#';
+        }
+        bytes[self.start_point.y as usize * row_length + self.start_point.x as usize] = b'^';
+
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        find_extents,
+        generate::{generate, MapOptions, SplitMix64},
+        sparse::LoopDetection,
+    };
+
+    const INPUT: &[u8] = include_bytes!("input.txt");
+
+    /// Both tables hold the same obsticles, the ones in `cells`
+    fn assert_consistent(grid: &SparseGrid, cells: &[Vec<bool>]) {
+        let mut from_columns = Vec::new();
+        for (column, x) in grid.x_to_y.iter().zip(0_u32..) {
+            from_columns.extend(column.as_slice().iter().map(|&y| Point::new(x, y as u32)));
+        }
+        from_columns.sort_unstable_by_key(|p| (p.y, p.x));
+
+        let expected = (0..grid.height)
+            .flat_map(|y| (0..grid.width).map(move |x| Point::new(x, y)))
+            .filter(|p| cells[p.y as usize][p.x as usize])
+            .collect::<Vec<_>>();
+
+        assert_eq!(grid.obsticles(), expected);
+        assert_eq!(from_columns, expected);
+        assert_eq!(grid.x_to_y.iter().count(), grid.width as usize);
+        assert_eq!(grid.y_to_x.iter().count(), grid.height as usize);
+    }
+
+    #[test]
+    fn test_to_bytes_round_trips() {
+        assert_eq!(SparseGrid::from_bytes(INPUT).to_bytes(), INPUT);
+
+        for seed in 0..20 {
+            let map = generate(
+                seed,
+                &MapOptions {
+                    max_obsticles_per_line: Some(MAX_OBSTICLES_PER_LINE as u32),
+                    ..MapOptions::new(1 + seed as u32 * 3, 1 + seed as u32 * 2, 0.2)
+                },
+            );
+            let grid = SparseGrid::try_from_bytes(&map.bytes).unwrap();
+            assert_eq!(grid.to_bytes(), map.bytes, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn test_edits() {
+        let mut grid = SparseGrid::from_bytes(b"..#\n.^.\n");
+
+        assert_eq!(grid.add_obsticle(Point::new(0, 1)), Ok(true));
+        assert_eq!(grid.add_obsticle(Point::new(0, 1)), Ok(false));
+        assert_eq!(
+            grid.add_obsticle(Point::new(1, 1)),
+            Err(EditError::Occupied(Point::new(1, 1)))
+        );
+        assert_eq!(
+            grid.add_obsticle(Point::new(3, 0)),
+            Err(EditError::OutOfBounds(Point::new(3, 0)))
+        );
+        assert_eq!(grid.to_bytes(), b"..#\n#^.\n");
+
+        assert!(grid.remove_obsticle(Point::new(2, 0)));
+        assert!(!grid.remove_obsticle(Point::new(2, 0)));
+        assert_eq!(
+            grid.set_start(Point::new(0, 1)),
+            Err(EditError::Occupied(Point::new(0, 1)))
+        );
+        assert_eq!(grid.set_start(Point::new(2, 0)), Ok(()));
+        assert_eq!(grid.to_bytes(), b"..^\n#..\n");
+
+        assert_eq!(grid.resize(2, 3), Err(EditError::StartOutOfBounds));
+        assert_eq!(
+            grid.resize(MAX_SIZE + 1, 3),
+            Err(EditError::TooBig {
+                width: MAX_SIZE + 1,
+                height: 3
+            })
+        );
+        assert_eq!(grid.to_bytes(), b"..^\n#..\n");
+
+        assert_eq!(grid.resize(4, 3), Ok(()));
+        assert_eq!(grid.add_obsticle(Point::new(3, 2)), Ok(true));
+        assert_eq!(grid.to_bytes(), b"..^.\n#...\n...#\n");
+
+        grid.set_start(Point::new(1, 0)).unwrap();
+        assert_eq!(grid.resize(2, 1), Ok(()));
+        assert_eq!(grid.to_bytes(), b".^\n");
+        assert_consistent(&grid, &[vec![false, false]]);
+    }
+
+    #[test]
+    fn test_solve_after_edits() {
+        let mut grid = SparseGrid::from_bytes(include_bytes!("example.txt"));
+
+        // Straight up and off the top
+        assert!(grid.remove_obsticle(Point::new(4, 0)));
+        assert_eq!(grid.part_1(), 7);
+
+        assert_eq!(grid.add_obsticle(Point::new(4, 0)), Ok(true));
+        assert_eq!(grid.part_1(), 41);
+        assert_eq!(grid.part_2(LoopDetection::TurnGraph), 6);
+    }
+
+    #[test]
+    fn test_line_limits() {
+        let mut grid =
+            SparseGrid::from_bytes(&[&[b'.'; 30][..], b"\n^", &[b'.'; 29], b"\n"].concat());
+
+        for x in 0..MAX_OBSTICLES_PER_LINE as u32 {
+            grid.add_obsticle(Point::new(x, 0)).unwrap();
+        }
+        assert_eq!(
+            grid.add_obsticle(Point::new(29, 0)),
+            Err(EditError::TooManyObsticlesInRow { row: 0 })
+        );
+
+        grid.resize(30, 30).unwrap();
+        for y in 1..MAX_OBSTICLES_PER_LINE as u32 {
+            grid.add_obsticle(Point::new(5, y + 1)).unwrap();
+        }
+        assert_eq!(
+            grid.add_obsticle(Point::new(5, 29)),
+            Err(EditError::TooManyObsticlesInColumn { column: 5 })
+        );
+
+        // Part 2 blocks cells below the full column on the way up to it
+        grid.set_start(Point::new(5, 28)).unwrap();
+        for detection in [
+            LoopDetection::HashSet,
+            LoopDetection::Brent,
+            LoopDetection::TurnGraph,
+        ] {
+            assert_eq!(grid.part_2(detection), 0);
+        }
+    }
+
+    #[test]
+    fn test_random_edits() {
+        let mut rng = SplitMix64::new(6);
+        let mut grid = SparseGrid::from_bytes(INPUT);
+        let extents = find_extents(INPUT);
+
+        // Plain grid of cells to check against
+        let mut cells = INPUT
+            .split(|c| *c == b'\n')
+            .take(grid.height as usize)
+            .map(|row| row.iter().map(|c| *c == b'#').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        for _ in 0..2000 {
+            let p = Point::new(
+                rng.range(0, extents.width - 1),
+                rng.range(0, extents.height - 1),
+            );
+
+            if rng.chance(0.5) {
+                match grid.add_obsticle(p) {
+                    Ok(added) => {
+                        assert_eq!(added, !cells[p.y as usize][p.x as usize]);
+                        cells[p.y as usize][p.x as usize] = true;
+                    }
+                    Err(EditError::Occupied(_)) => assert_eq!(p, grid.start_point),
+                    Err(_) => {}
+                }
+            } else if rng.chance(0.9) {
+                assert_eq!(grid.remove_obsticle(p), cells[p.y as usize][p.x as usize]);
+                cells[p.y as usize][p.x as usize] = false;
+            } else if grid.set_start(p).is_ok() {
+                assert!(!cells[p.y as usize][p.x as usize]);
+            }
+        }
+
+        assert_consistent(&grid, &cells);
+
+        let reparsed = SparseGrid::try_from_bytes(&grid.to_bytes()).unwrap();
+        assert_eq!(reparsed.start_point, grid.start_point);
+        assert_consistent(&reparsed, &cells);
+    }
+}
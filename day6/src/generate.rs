@@ -0,0 +1,357 @@
+use std::{fs, io, path::Path};
+
+use crate::point::Point;
+
+/// Tiny seeded PRNG so generated maps are reproducible without pulling in `rand`.
+/// SplitMix64 is more than good enough for scattering obsticles.
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `low..=high`
+    pub fn range(&mut self, low: u32, high: u32) -> u32 {
+        let span = (high - low) as u64 + 1;
+        low + (self.next_u64() % span) as u32
+    }
+
+    /// True with probability `p`
+    pub fn chance(&mut self, p: f64) -> bool {
+        // Top 53 bits give a uniform f64 in 0..1
+        ((self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64) < p
+    }
+}
+
+/// What the guard must do on the generated map.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GuardOutcome {
+    /// Whatever the random obsticles lead to
+    Any,
+    /// The column above the start is kept clear so the guard walks straight off the top
+    Exits,
+    /// A rectangle of obsticles is built around the start so the guard goes round it forever
+    Loops,
+}
+
+pub struct MapOptions {
+    pub width: u32,
+    pub height: u32,
+    /// Chance of any free cell being an obsticle
+    pub density: f64,
+    pub outcome: GuardOutcome,
+    /// Cap on obsticles per row and per column. `SparseGrid` holds at most
+    /// `MAX_OBSTICLES_PER_LINE` per line (and only handles maps up to `MAX_SIZE`
+    /// wide) so set this when generating for it.
+    pub max_obsticles_per_line: Option<u32>,
+}
+
+impl MapOptions {
+    pub fn new(width: u32, height: u32, density: f64) -> Self {
+        Self {
+            width,
+            height,
+            density,
+            outcome: GuardOutcome::Any,
+            max_obsticles_per_line: None,
+        }
+    }
+}
+
+pub struct GeneratedMap {
+    pub width: u32,
+    pub height: u32,
+    pub start_point: Point,
+    /// The map in puzzle input format, every row terminated by a newline
+    pub bytes: Vec<u8>,
+}
+
+impl GeneratedMap {
+    pub fn write_to(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, &self.bytes)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        self.bytes
+            .chunks(self.width as usize + 1)
+            .map(|row| &row[..row.len() - 1])
+    }
+}
+
+const FREE: u8 = 0;
+const OBSTICLE: u8 = 1;
+// Must stay clear to guarantee the outcome
+const LOCKED: u8 = 2;
+
+/// Generate a valid day 6 map with exactly one guard. The same seed and options
+/// always produce the same map.
+///
+/// Panics if the map is too small to hold the requested outcome, which needs at
+/// least a 3x3 map for `GuardOutcome::Loops`.
+pub fn generate(seed: u64, options: &MapOptions) -> GeneratedMap {
+    let MapOptions { width, height, .. } = *options;
+    assert!(width >= 1 && height >= 1, "map must have at least one cell");
+
+    let mut rng = SplitMix64::new(seed);
+    let mut cells = vec![FREE; (width * height) as usize];
+    let idx = |x: u32, y: u32| (y * width + x) as usize;
+
+    let start_point = match options.outcome {
+        GuardOutcome::Any => Point::new(rng.range(0, width - 1), rng.range(0, height - 1)),
+        GuardOutcome::Exits => {
+            let start = Point::new(rng.range(0, width - 1), rng.range(0, height - 1));
+            for y in 0..start.y {
+                cells[idx(start.x, y)] = LOCKED;
+            }
+            start
+        }
+        GuardOutcome::Loops => {
+            assert!(
+                width >= 3 && height >= 3,
+                "need at least a 3x3 map to build a loop"
+            );
+
+            let start = Point::new(rng.range(1, width - 2), rng.range(1, height - 2));
+
+            // The guard walks up to `top`, then round the rectangle between
+            // `left..=right` and `top..=bottom`, turning at each corner
+            let top = rng.range(1, start.y);
+            let bottom = rng.range(start.y, height - 2);
+            let left = rng.range(1, start.x);
+            let right = rng.range(start.x, width - 2);
+
+            for x in left..=right {
+                cells[idx(x, top)] = LOCKED;
+                cells[idx(x, bottom)] = LOCKED;
+            }
+            for y in top..=bottom {
+                cells[idx(left, y)] = LOCKED;
+                cells[idx(right, y)] = LOCKED;
+            }
+            for y in top..=start.y {
+                cells[idx(start.x, y)] = LOCKED;
+            }
+
+            for (x, y) in [
+                (start.x, top - 1),
+                (right + 1, top),
+                (right, bottom + 1),
+                (left - 1, bottom),
+                (left, top - 1),
+            ] {
+                cells[idx(x, y)] = OBSTICLE;
+            }
+
+            start
+        }
+    };
+    cells[idx(start_point.x, start_point.y)] = LOCKED;
+
+    let mut per_row = vec![0_u32; height as usize];
+    let mut per_column = vec![0_u32; width as usize];
+    for y in 0..height {
+        for x in 0..width {
+            if cells[idx(x, y)] == OBSTICLE {
+                per_row[y as usize] += 1;
+                per_column[x as usize] += 1;
+            }
+        }
+    }
+
+    let max_per_line = options.max_obsticles_per_line.unwrap_or(u32::MAX);
+
+    for y in 0..height {
+        for x in 0..width {
+            if cells[idx(x, y)] != FREE
+                || per_row[y as usize] >= max_per_line
+                || per_column[x as usize] >= max_per_line
+            {
+                continue;
+            }
+
+            if rng.chance(options.density) {
+                cells[idx(x, y)] = OBSTICLE;
+                per_row[y as usize] += 1;
+                per_column[x as usize] += 1;
+            }
+        }
+    }
+
+    let mut bytes = Vec::with_capacity(((width + 1) * height) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let byte = if Point::new(x, y) == start_point {
+                b'^'
+            } else if cells[idx(x, y)] == OBSTICLE {
+                b'#'
+            } else {
+                b'.'
+            };
+            bytes.push(byte);
+        }
+        bytes.push(b'\n');
+    }
+
+    GeneratedMap {
+        width,
+        height,
+        start_point,
+        bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "bench")]
+    extern crate test;
+    use super::*;
+    use crate::{
+        direction::Direction,
+        find_start_simd,
+        sparse::{SparseGrid, MAX_OBSTICLES_PER_LINE},
+        turn_graph::{TurnGraph, TurnStatus},
+    };
+    #[cfg(feature = "bench")]
+    use test::Bencher;
+
+    fn sparse_options(outcome: GuardOutcome) -> MapOptions {
+        MapOptions {
+            outcome,
+            max_obsticles_per_line: Some(MAX_OBSTICLES_PER_LINE as u32),
+            ..MapOptions::new(130, 130, 0.08)
+        }
+    }
+
+    #[test]
+    fn test_generate_is_valid() {
+        for seed in 0..20 {
+            let map = generate(seed, &MapOptions::new(50, 30, 0.2));
+
+            assert_eq!(map.bytes.len(), 51 * 30);
+            assert_eq!(map.bytes.iter().filter(|c| **c == b'^').count(), 1);
+            assert_eq!(map.rows().count(), 30);
+            assert!(map.rows().all(|row| row.len() == 50));
+            assert_eq!(find_start_simd(&map.bytes, 50), Some(map.start_point));
+        }
+    }
+
+    #[test]
+    fn test_generate_is_seeded() {
+        let options = MapOptions::new(40, 40, 0.1);
+        assert_eq!(generate(7, &options).bytes, generate(7, &options).bytes);
+        assert_ne!(generate(7, &options).bytes, generate(8, &options).bytes);
+    }
+
+    #[test]
+    fn test_generate_outcome() {
+        for seed in 0..20 {
+            let exits =
+                SparseGrid::from_bytes(&generate(seed, &sparse_options(GuardOutcome::Exits)).bytes);
+            assert_eq!(exits.next_turn((exits.start_point, Direction::Up)), None);
+
+            let loops =
+                SparseGrid::from_bytes(&generate(seed, &sparse_options(GuardOutcome::Loops)).bytes);
+            let first_turn = loops
+                .next_turn((loops.start_point, Direction::Up))
+                .expect("turns before leaving");
+            let status = TurnGraph::from_grid(&loops).status(first_turn);
+            assert!(
+                matches!(status, Some(TurnStatus::Loops { .. })),
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_max_obsticles_per_line() {
+        let map = generate(
+            3,
+            &MapOptions {
+                max_obsticles_per_line: Some(5),
+                ..MapOptions::new(100, 100, 0.5)
+            },
+        );
+
+        for row in map.rows() {
+            assert!(row.iter().filter(|c| **c == b'#').count() <= 5);
+        }
+    }
+
+    #[cfg(feature = "bench")]
+    fn dense_part_1(map: &GeneratedMap) -> usize {
+        use crate::{find_extents, part_1};
+
+        let extents = find_extents(&map.bytes);
+        let grid = map.rows().collect::<Vec<_>>();
+        part_1(&grid, map.start_point, extents)
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_250_dense_part_1(b: &mut Bencher) {
+        let map = generate(
+            1,
+            &MapOptions {
+                max_obsticles_per_line: Some(MAX_OBSTICLES_PER_LINE as u32),
+                ..MapOptions::new(250, 250, 0.04)
+            },
+        );
+        b.iter(|| dense_part_1(&map));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_250_sparse_part_1(b: &mut Bencher) {
+        let map = generate(
+            1,
+            &MapOptions {
+                max_obsticles_per_line: Some(MAX_OBSTICLES_PER_LINE as u32),
+                ..MapOptions::new(250, 250, 0.04)
+            },
+        );
+        b.iter(|| SparseGrid::from_bytes(&map.bytes).part_1());
+    }
+
+    #[cfg(feature = "bench")]
+    fn generated_1000() -> GeneratedMap {
+        generate(
+            1,
+            &MapOptions {
+                max_obsticles_per_line: Some(MAX_OBSTICLES_PER_LINE as u32),
+                ..MapOptions::new(1000, 1000, 0.01)
+            },
+        )
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_1000_dense_part_1(b: &mut Bencher) {
+        let map = generated_1000();
+        b.iter(|| dense_part_1(&map));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_1000_sparse_part_1(b: &mut Bencher) {
+        let map = generated_1000();
+        b.iter(|| SparseGrid::from_bytes(&map.bytes).part_1());
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_generated_1000_sparse_part_2(b: &mut Bencher) {
+        use crate::sparse::LoopDetection;
+
+        let map = generated_1000();
+        b.iter(|| SparseGrid::from_bytes(&map.bytes).part_2(LoopDetection::TurnGraph));
+    }
+}
@@ -0,0 +1,270 @@
+//! Where the guard goes and which way it's facing there, for spotting the
+//! corridors it keeps coming back to.
+
+use std::io::{self, Write};
+
+use crate::{direction::Direction, point::Point, sparse::SparseGrid};
+
+/// What the guard did in one cell
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CellVisits {
+    /// Times the guard arrived here, including starting here
+    pub visits: u32,
+    /// `Direction` flags for every way it faced while here
+    pub directions: u8,
+}
+
+impl CellVisits {
+    pub fn faced(&self, direction: Direction) -> bool {
+        self.directions & direction as u8 != 0
+    }
+
+    fn vertical(&self) -> bool {
+        self.faced(Direction::Up) || self.faced(Direction::Down)
+    }
+
+    fn horizontal(&self) -> bool {
+        self.faced(Direction::Left) || self.faced(Direction::Right)
+    }
+}
+
+pub struct Heatmap {
+    pub width: u32,
+    pub height: u32,
+    /// Row by row
+    cells: Vec<CellVisits>,
+    obsticles: Vec<Point>,
+    /// The guard went round a loop rather than leaving the map. Counting stops
+    /// the first time it's back in a cell facing the same way.
+    pub looped: bool,
+}
+
+impl Heatmap {
+    pub fn get(&self, p: Point) -> CellVisits {
+        self.cells[(p.y * self.width + p.x) as usize]
+    }
+
+    fn get_mut(&mut self, p: Point) -> &mut CellVisits {
+        &mut self.cells[(p.y * self.width + p.x) as usize]
+    }
+
+    /// Every cell the guard went through, row by row
+    pub fn visited(&self) -> impl Iterator<Item = (Point, CellVisits)> + '_ {
+        (0..self.height)
+            .flat_map(move |y| (0..self.width).map(move |x| Point::new(x, y)))
+            .map(|p| (p, self.get(p)))
+            .filter(|(_, cell)| cell.visits > 0)
+    }
+
+    pub fn max_visits(&self) -> u32 {
+        self.cells.iter().map(|cell| cell.visits).max().unwrap_or(0)
+    }
+
+    /// 0 for unvisited up to 255 for cells visited `max_visits` times
+    fn brightness(cell: CellVisits, max_visits: u32) -> u8 {
+        (cell.visits * 255 / max_visits.max(1)) as u8
+    }
+
+    /// Binary greyscale PGM of the visit counts
+    pub fn write_pgm(&self, mut writer: impl Write) -> io::Result<()> {
+        write!(writer, "P5\n{} {}\n255\n", self.width, self.height)?;
+
+        let max_visits = self.max_visits();
+        let pixels = self
+            .cells
+            .iter()
+            .map(|cell| Self::brightness(*cell, max_visits))
+            .collect::<Vec<_>>();
+        writer.write_all(&pixels)
+    }
+
+    /// Binary PPM with the guard's vertical runs in red and horizontal ones in
+    /// green, so cells it crosses both ways come out yellow. Brighter cells were
+    /// visited more, obsticles are grey.
+    pub fn write_ppm(&self, mut writer: impl Write) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+
+        let max_visits = self.max_visits();
+        let mut pixels = Vec::with_capacity(self.cells.len() * 3);
+        for cell in &self.cells {
+            // Keep even a single visit visible against the black background
+            let level = match cell.visits {
+                0 => 0,
+                _ => 64 + (Self::brightness(*cell, max_visits) as u32 * 191 / 255) as u8,
+            };
+            let red = if cell.vertical() { level } else { 0 };
+            let green = if cell.horizontal() { level } else { 0 };
+            pixels.extend_from_slice(&[red, green, 0]);
+        }
+        for p in &self.obsticles {
+            let i = (p.y * self.width + p.x) as usize * 3;
+            pixels[i..i + 3].copy_from_slice(&[128, 128, 128]);
+        }
+
+        writer.write_all(&pixels)
+    }
+
+    /// The visit counts as a matrix, one line per row and no header
+    pub fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        for row in self.cells.chunks(self.width as usize) {
+            let counts = row
+                .iter()
+                .map(|cell| cell.visits.to_string())
+                .collect::<Vec<_>>();
+            writeln!(writer, "{}", counts.join(","))?;
+        }
+        Ok(())
+    }
+
+    /// One `x,y,visits,up,right,down,left` row per visited cell, with a header.
+    /// The direction columns are 1 if the guard faced that way there.
+    pub fn write_directions_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "x,y,visits,up,right,down,left")?;
+        for (p, cell) in self.visited() {
+            let [up, right, down, left] = [
+                Direction::Up,
+                Direction::Right,
+                Direction::Down,
+                Direction::Left,
+            ]
+            .map(|direction| cell.faced(direction) as u8);
+            writeln!(
+                writer,
+                "{},{},{},{up},{right},{down},{left}",
+                p.x, p.y, cell.visits
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl SparseGrid {
+    /// Walk the guard one cell at a time from the start, recording every cell
+    /// it passes through until it leaves the map or starts repeating itself
+    pub fn heatmap(&self) -> Heatmap {
+        let mut heatmap = Heatmap {
+            width: self.width,
+            height: self.height,
+            cells: vec![CellVisits::default(); (self.width * self.height) as usize],
+            obsticles: self.obsticles(),
+            looped: false,
+        };
+
+        let mut position = self.start_point;
+        let mut direction = Direction::Up;
+        heatmap.get_mut(position).visits += 1;
+
+        loop {
+            let cell = heatmap.get_mut(position);
+            if cell.faced(direction) {
+                heatmap.looped = true;
+                break;
+            }
+            cell.directions |= direction as u8;
+
+            // Off the top or left wraps round to u32::MAX
+            let next = position.step(direction);
+            if next.x >= self.width || next.y >= self.height {
+                break;
+            }
+
+            if self.is_obsticle(next) {
+                direction = direction.rotate_clockwise();
+            } else {
+                position = next;
+                heatmap.get_mut(position).visits += 1;
+            }
+        }
+
+        heatmap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answers::expected;
+
+    const INPUT: &[u8] = include_bytes!("input.txt");
+    const EXAMPLE: &[u8] = include_bytes!("example.txt");
+
+    #[test]
+    fn test_visited_matches_part_1() {
+        for input in [INPUT, EXAMPLE] {
+            let heatmap = SparseGrid::from_bytes(input).heatmap();
+            assert!(!heatmap.looped);
+            assert_eq!(
+                heatmap.visited().count(),
+                expected::<usize>(input, "part_1")
+            );
+        }
+    }
+
+    #[test]
+    fn test_example() {
+        let heatmap = SparseGrid::from_bytes(EXAMPLE).heatmap();
+
+        // Where the guard starts, then comes back through going left
+        let start = heatmap.get(Point::new(4, 6));
+        assert_eq!(start.visits, 2);
+        assert_eq!(
+            start.directions,
+            Direction::Up as u8 | Direction::Left as u8
+        );
+
+        // Turns on the spot, so one visit facing two ways
+        let corner = heatmap.get(Point::new(4, 1));
+        assert_eq!(corner.visits, 1);
+        assert_eq!(
+            corner.directions,
+            Direction::Up as u8 | Direction::Right as u8
+        );
+
+        assert_eq!(heatmap.get(Point::new(0, 0)), CellVisits::default());
+        assert_eq!(heatmap.max_visits(), 2);
+    }
+
+    #[test]
+    fn test_loop() {
+        let heatmap = SparseGrid::from_bytes(b".#...\n....#\n.....\n#^...\n...#.\n").heatmap();
+
+        assert!(heatmap.looped);
+        // Started here, then came back round from the right and noticed it was
+        // facing up here again
+        assert_eq!(heatmap.get(Point::new(1, 3)).visits, 2);
+        assert_eq!(heatmap.get(Point::new(1, 2)).visits, 1);
+        assert_eq!(
+            heatmap.get(Point::new(3, 1)).directions,
+            Direction::Right as u8 | Direction::Down as u8
+        );
+        assert_eq!(heatmap.visited().count(), 8);
+    }
+
+    #[test]
+    fn test_exports() {
+        let heatmap = SparseGrid::from_bytes(b"#..\n.^.\n").heatmap();
+
+        let mut pgm = Vec::new();
+        heatmap.write_pgm(&mut pgm).unwrap();
+        assert_eq!(pgm, b"P5\n3 2\n255\n\0\xff\0\0\xff\0");
+
+        let mut ppm = Vec::new();
+        heatmap.write_ppm(&mut ppm).unwrap();
+        assert_eq!(&ppm[..11], b"P6\n3 2\n255\n");
+        assert_eq!(
+            ppm[11..],
+            [128, 128, 128, 255, 0, 0, 0, 0, 0, 0, 0, 0, 255, 0, 0, 0, 0, 0]
+        );
+
+        let mut csv = Vec::new();
+        heatmap.write_csv(&mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "0,1,0\n0,1,0\n");
+
+        let mut csv = Vec::new();
+        heatmap.write_directions_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "x,y,visits,up,right,down,left\n1,0,1,1,0,0,0\n1,1,1,1,0,0,0\n"
+        );
+    }
+}
@@ -1,22 +1,71 @@
-#![feature(test, portable_simd)]
-
-mod direction;
-mod point;
-mod rectangle;
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+#![cfg_attr(all(test, feature = "bench"), feature(test))]
+
+pub mod answers;
+pub mod binary;
+pub mod direction;
+pub mod edit;
+pub mod generate;
+pub mod heatmap;
+pub mod point;
+pub mod rectangle;
+pub mod report;
 mod simd_grid;
-mod sparse;
+pub mod sparse;
+pub mod turn_graph;
 
-use std::{
-    collections::{HashMap, HashSet},
-    simd::{cmp::SimdPartialEq, Simd},
-};
+pub use aoc_shared::dispatch;
+use std::collections::HashSet;
+#[cfg(feature = "simd")]
+use std::simd::{cmp::SimdPartialEq, Simd};
 
 use direction::Direction;
 use point::Point;
 use rectangle::Rectangle;
 
+#[cfg(feature = "simd")]
 pub type SimdBlock = Simd<u8, 64>;
 
+/// Up to 64 bytes of input, padded with zeroes, for the chunked scans. A
+/// `SimdBlock` with the `simd` feature and a plain array without it.
+#[derive(Copy, Clone)]
+pub(crate) struct Block(
+    #[cfg(feature = "simd")] SimdBlock,
+    #[cfg(not(feature = "simd"))] [u8; 64],
+);
+
+impl Block {
+    #[inline(always)]
+    pub(crate) fn load(chunk: &[u8]) -> Self {
+        #[cfg(feature = "simd")]
+        {
+            Self(SimdBlock::load_or_default(chunk))
+        }
+        #[cfg(not(feature = "simd"))]
+        {
+            let mut bytes = [0; 64];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            Self(bytes)
+        }
+    }
+
+    /// Bit `i` is set if byte `i` is `byte`
+    #[inline(always)]
+    pub(crate) fn mask(self, byte: u8) -> u64 {
+        #[cfg(feature = "simd")]
+        {
+            self.0.simd_eq(SimdBlock::splat(byte)).to_bitmask()
+        }
+        #[cfg(not(feature = "simd"))]
+        {
+            self.0
+                .iter()
+                .enumerate()
+                .fold(0, |mask, (i, &c)| mask | ((c == byte) as u64) << i)
+        }
+    }
+}
+
 pub fn find_width(input: &[u8]) -> u32 {
     input
         .iter()
@@ -53,24 +102,28 @@ pub fn find_start(input: &[u8], width: u32) -> Option<Point> {
         .map(|i| Point::new(i as u32 % (width + 1), i as u32 / (width + 1)))
 }
 
-pub fn find_start_simd(input: &[u8], width: u32) -> Option<Point> {
-    let target = SimdBlock::splat(b'^');
+dispatch::multiversion! {
+    pub fn find_start_simd(input: &[u8], width: u32) -> Option<Point> {
+        for (chunk_idx, c) in input.chunks(64).enumerate() {
+            let mask = Block::load(c).mask(b'^');
 
-    for (chunk_idx, c) in input.chunks(64).enumerate() {
-        let chunk = SimdBlock::load_or_default(c);
-        let mask = chunk.simd_eq(target).to_bitmask();
-
-        if mask != 0 {
-            let idx = mask.trailing_zeros();
-            let i = chunk_idx as u32 * 64 + idx;
-            return Some(Point::new(i % (width + 1), i / (width + 1)));
+            if mask != 0 {
+                let idx = mask.trailing_zeros();
+                let i = chunk_idx as u32 * 64 + idx;
+                return Some(Point::new(i % (width + 1), i / (width + 1)));
+            }
         }
+
+        None
     }
+}
 
-    None
+pub fn part_1(input: &[&[u8]], position: Point, extents: Rectangle) -> usize {
+    visited(input, position, extents).len()
 }
 
-pub fn part_1(input: &[&[u8]], mut position: Point, extents: Rectangle) -> usize {
+/// Every cell the guard walks over before leaving the map
+pub fn visited(input: &[&[u8]], mut position: Point, extents: Rectangle) -> HashSet<Point> {
     let mut direction = Direction::Up;
 
     let mut visited: HashSet<Point> = HashSet::new();
@@ -93,48 +146,96 @@ pub fn part_1(input: &[&[u8]], mut position: Point, extents: Rectangle) -> usize
         }
     }
 
-    visited.len()
+    visited
 }
 
+/// Walks the route once and, the first time the guard reaches each cell, tries a
+/// new obsticle there and follows the guard on from where it's stood. A cell the
+/// guard has already walked through can't take an obsticle without changing the
+/// route before it.
 pub fn part_2(input: &[&[u8]], mut position: Point, extents: Rectangle) -> usize {
     let mut direction = Direction::Up;
 
-    let mut visited: HashMap<Point, u8> = HashMap::new();
+    let mut tried: HashSet<Point> = HashSet::new();
+    tried.insert(position);
+
+    let mut count = 0;
 
     loop {
         let next_pos = position.step(direction);
 
-        let direction_flags = visited
-            .get(&next_pos)
-            .map(|&existing_direction| existing_direction | direction as u8)
-            .unwrap_or(direction as u8);
+        if !extents.contains(next_pos) {
+            return count;
+        }
 
-        visited.insert(next_pos, direction_flags);
+        if input[next_pos.y as usize][next_pos.x as usize] == b'#' {
+            direction = direction.rotate_clockwise();
+            continue;
+        }
 
-        if extents.contains(next_pos) {
-            let next_tile = input[next_pos.y as usize][next_pos.x as usize];
+        if tried.insert(next_pos) && is_loop_from(input, position, direction, extents, next_pos) {
+            count += 1;
+        }
 
-            if next_tile == b'#' {
-                direction = direction.rotate_clockwise();
-            } else {
-                position = next_pos;
-            }
+        position = next_pos;
+    }
+}
+
+/// Brute force part 2. Tries a new obsticle on every cell the guard visits (other
+/// than the start) and walks the whole route again from the start. Slow, but simple
+/// enough to check the clever versions against.
+pub fn part_2_obsticles(input: &[&[u8]], start: Point, extents: Rectangle) -> Vec<Point> {
+    let mut obsticles = visited(input, start, extents)
+        .into_iter()
+        .filter(|&candidate| candidate != start && is_loop(input, start, extents, candidate))
+        .collect::<Vec<_>>();
+
+    obsticles.sort_unstable_by_key(|p| (p.y, p.x));
+    obsticles
+}
+
+fn is_loop(input: &[&[u8]], position: Point, extents: Rectangle, obsticle: Point) -> bool {
+    is_loop_from(input, position, Direction::Up, extents, obsticle)
+}
+
+fn is_loop_from(
+    input: &[&[u8]],
+    mut position: Point,
+    mut direction: Direction,
+    extents: Rectangle,
+    obsticle: Point,
+) -> bool {
+    let mut visited: HashSet<(Point, Direction)> = HashSet::new();
+
+    loop {
+        if !visited.insert((position, direction)) {
+            return true;
+        }
+
+        let next_pos = position.step(direction);
+
+        if !extents.contains(next_pos) {
+            return false;
+        }
+
+        if next_pos == obsticle || input[next_pos.y as usize][next_pos.x as usize] == b'#' {
+            direction = direction.rotate_clockwise();
         } else {
-            break;
+            position = next_pos;
         }
     }
-
-    visited
-        .values()
-        .map(|direction_flags| Direction::potential_blocker_count(*direction_flags) as usize)
-        .inspect(|v| println!("{}", v))
-        .sum()
 }
 
+#[cfg(test)]
+mod differential;
+
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "bench")]
     extern crate test;
     use super::*;
+    use answers::expected;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     const INPUT: &[u8] = include_bytes!("input.txt");
@@ -155,13 +256,46 @@ mod tests {
     #[test]
     fn test_find_start() {
         let p = find_start(INPUT, find_width(INPUT)).expect("find the start");
-        assert_eq!(p, Point::new(41, 73));
+        assert_eq!(p, expected(INPUT, "start"));
     }
 
     #[test]
     fn test_find_start_simd() {
         let p = find_start_simd(INPUT, find_width(INPUT)).expect("find the start");
-        assert_eq!(p, Point::new(41, 73));
+        assert_eq!(p, expected(INPUT, "start"));
+    }
+
+    #[test]
+    fn test_block_mask() {
+        let block = Block::load(b"#..^\n#");
+        assert_eq!(block.mask(b'#'), 0b100001);
+        assert_eq!(block.mask(b'^'), 0b1000);
+        assert_eq!(Block::load(&[b'.'; 64]).mask(b'.'), u64::MAX);
+    }
+
+    #[test]
+    fn test_find_start_simd_every_level() {
+        let maps =
+            (0..20).map(|seed| generate::generate(seed, &generate::MapOptions::new(70, 40, 0.1)));
+
+        for level in dispatch::supported() {
+            dispatch::with_level(level, || {
+                assert_eq!(
+                    find_start_simd(INPUT, 130),
+                    Some(expected(INPUT, "start")),
+                    "{level:?}"
+                );
+
+                for map in maps.clone() {
+                    assert_eq!(
+                        find_start_simd(&map.bytes, 70),
+                        Some(map.start_point),
+                        "{level:?}"
+                    );
+                }
+                assert_eq!(find_start_simd(b"..\n..\n", 2), None, "{level:?}");
+            });
+        }
     }
 
     #[test]
@@ -174,44 +308,66 @@ mod tests {
 
         let count = part_1(&grid, position, extents);
 
-        assert_eq!(count, 4939);
+        assert_eq!(count, expected(INPUT, "part_1"));
     }
 
-    // #[test]
-    // fn test_part_2() {
-    //     let extents = find_extents(INPUT);
-
-    //     let position = find_start_simd(INPUT, find_width(INPUT)).expect("find the start");
-
-    //     let grid = INPUT.split(|c| *c == b'\n').collect::<Vec<_>>();
-
-    //     let count = part_2(&grid, position, extents);
-
-    //     assert_eq!(count, 4939);
-    // }
+    #[test]
+    fn test_part_2() {
+        for input in [INPUT, &include_bytes!("example.txt")[..]] {
+            let extents = find_extents(input);
+            let position = find_start_simd(input, find_width(input)).expect("find the start");
+            let grid = input.split(|c| *c == b'\n').collect::<Vec<_>>();
 
-    // #[test]
-    // fn test_part_2() {
-    //     let extents = find_extents(INPUT);
-    //     let position = find_start_simd(INPUT, find_width(INPUT)).expect("find the start");
-    //     let grid = INPUT.split(|c| *c == b'\n').collect::<Vec<_>>();
-    //     let count = part_2(&grid, position, extents.x, extents.y);
+            let count = part_2(&grid, position, extents);
 
-    //     assert_eq!(count, 0);
-    // }
+            assert_eq!(count, expected(input, "part_2"));
+        }
+    }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_find_start(b: &mut Bencher) {
         b.iter(|| find_start(INPUT, 130).expect("find the start"));
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_find_start_simd(b: &mut Bencher) {
         b.iter(|| find_start_simd(INPUT, 130).expect("find the start"));
     }
 
+    #[cfg(feature = "bench")]
+    fn bench_find_start_simd_level(b: &mut Bencher, level: dispatch::Level) {
+        // Benches the detected level instead if this CPU can't run `level`
+        let level = level.min(dispatch::detected());
+        dispatch::with_level(level, || {
+            b.iter(|| find_start_simd(test::black_box(INPUT), 130).expect("find the start"))
+        });
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_find_start_simd_baseline(b: &mut Bencher) {
+        bench_find_start_simd_level(b, dispatch::Level::Baseline);
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_find_start_simd_avx2(b: &mut Bencher) {
+        bench_find_start_simd_level(b, dispatch::Level::Avx2);
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_find_start_simd_avx512(b: &mut Bencher) {
+        bench_find_start_simd_level(b, dispatch::Level::Avx512);
+    }
+
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_prelude(b: &mut Bencher) {
+        let expected_start = expected(INPUT, "start");
+
         b.iter(|| {
             let extents = find_extents(INPUT);
 
@@ -219,14 +375,17 @@ mod tests {
 
             let grid = INPUT.split(|c| *c == b'\n').collect::<Vec<_>>();
 
-            assert_eq!(position, Point::new(41, 73));
+            assert_eq!(position, expected_start);
             assert_eq!(grid[0].len(), 130);
             assert_eq!(grid[129].len(), 130);
         });
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1_complete(b: &mut Bencher) {
+        let expected_count = expected(INPUT, "part_1");
+
         b.iter(|| {
             let extents = find_extents(INPUT);
 
@@ -236,7 +395,7 @@ mod tests {
 
             let count = part_1(&grid, position, extents);
 
-            assert_eq!(count, 4939);
+            assert_eq!(count, expected_count);
         });
     }
 }
@@ -1,3 +1,5 @@
+use std::{fmt, str::FromStr};
+
 use crate::direction::Direction;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -11,11 +13,13 @@ impl Point {
         Self { x, y }
     }
 
+    // Stepping off the top or left edge wraps around to u32::MAX, which is
+    // outside of any extents, rather than panicking
     pub fn step(&self, dir: Direction) -> Point {
         match dir {
-            Direction::Up => Point::new(self.x, self.y - 1),
+            Direction::Up => Point::new(self.x, self.y.wrapping_sub(1)),
             Direction::Down => Point::new(self.x, self.y + 1),
-            Direction::Left => Point::new(self.x - 1, self.y),
+            Direction::Left => Point::new(self.x.wrapping_sub(1), self.y),
             Direction::Right => Point::new(self.x + 1, self.y),
         }
     }
@@ -29,3 +33,23 @@ impl Point {
         }
     }
 }
+
+/// `x,y`
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl FromStr for Point {
+    type Err = ();
+
+    /// The `x,y` that `Display` writes
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y) = s.split_once(',').ok_or(())?;
+        Ok(Point::new(
+            x.parse().map_err(|_| ())?,
+            y.parse().map_err(|_| ())?,
+        ))
+    }
+}
@@ -20,6 +20,9 @@ impl Rectangle {
     }
 
     pub fn contains(&self, point: Point) -> bool {
-        point.x > self.x && point.y > self.y && point.x < self.width && point.y < self.height
+        point.x >= self.x
+            && point.y >= self.y
+            && point.x < self.x + self.width
+            && point.y < self.y + self.height
     }
 }
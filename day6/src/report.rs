@@ -0,0 +1,75 @@
+//! Loading a map by scanning its text against loading it from the binary
+//! format, for `cargo run --release --bin load_report` to time with
+//! [`aoc_shared::report`].
+
+use std::hint::black_box;
+
+pub use aoc_shared::report::{time, Report, Stats};
+
+use crate::{
+    dispatch::{self, Level},
+    sparse::{ParseError, SparseGrid},
+};
+
+fn text_row(level: Level) -> &'static str {
+    match level {
+        Level::Baseline => "text_baseline",
+        Level::Avx2 => "text_avx2",
+        Level::Avx512 => "text_avx512",
+    }
+}
+
+/// Time loading `input` as text at every level this CPU can run, then as
+/// binary, relative to the text at the detected level. Each row answers part 1
+/// on the grid it loaded, so they should all agree.
+pub fn load(input: &[u8], runs: usize) -> Result<Report<u32>, ParseError> {
+    let binary = SparseGrid::try_from_bytes(input)?.to_binary();
+    let mut rows = Vec::new();
+    let mut answers = Vec::new();
+    let mut row = |name, bytes, load: &dyn Fn() -> SparseGrid| {
+        answers.push(load().part_1());
+        rows.push(time(name, bytes, runs, load));
+    };
+
+    row("text", input.len(), &|| {
+        SparseGrid::from_bytes(black_box(input))
+    });
+    for level in dispatch::supported() {
+        row(text_row(level), input.len(), &|| {
+            dispatch::with_level(level, || SparseGrid::from_bytes(black_box(input)))
+        });
+    }
+    row("binary", binary.len(), &|| {
+        SparseGrid::from_binary(black_box(&binary)).expect("it was just saved")
+    });
+
+    Ok(Report {
+        rows,
+        answers,
+        baseline: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answers::expected;
+
+    const INPUT: &[u8] = include_bytes!("input.txt");
+
+    #[test]
+    fn test_load() {
+        assert!(load(b"..\n..\n", 1).is_err());
+
+        let report = load(INPUT, 3).unwrap();
+        let names = report.rows.iter().map(|row| row.name).collect::<Vec<_>>();
+        assert_eq!(names.len(), dispatch::supported().count() + 2);
+        assert_eq!(names[..2], ["text", "text_baseline"]);
+        assert_eq!(names.last(), Some(&"binary"));
+        assert!(report
+            .answers
+            .iter()
+            .all(|answer| *answer == expected::<u32>(INPUT, "part_1")));
+        assert_eq!(report.relative_speed(&report.rows[0]), 1.0);
+    }
+}
@@ -1,33 +1,64 @@
+#[cfg(all(test, feature = "bench"))]
 extern crate test;
 
-use std::simd::cmp::SimdPartialEq as _;
-
 use fnv::FnvHashSet;
 
-use crate::{direction::Direction, point::Point, SimdBlock};
+use crate::{direction::Direction, dispatch, point::Point, turn_graph::TurnGraph, Block};
+
+/// Room for obsticles in each row and column of the lookup tables
+const LINE_CAPACITY: usize = 24;
+
+/// Most obsticles a single row or column of a map can hold. One less than the
+/// tables have room for, part 2 adds a block to a line while it tests it.
+pub const MAX_OBSTICLES_PER_LINE: usize = LINE_CAPACITY - 1;
+
+/// Coordinates are stored as u16 so this is the biggest map we can hold
+pub const MAX_SIZE: u32 = u16::MAX as u32;
 
 // Extremely light weight set for the x -> y and y -> x mappings
 #[derive(Default)]
 pub struct SmallVec {
     length: usize,
-    // The most obsticles we have in a row is 17 but
-    // We don't get a smaller struct if we shrink-wrap to this
-    // due to the word alignment. We could try a #[repr(packed)]
-    // and see how that effects performance
-    data: [u8; 24],
+    // Maps get at most MAX_OBSTICLES_PER_LINE in a line, the spare slot is
+    // for part 2's block. The real input's fullest line has 17, so most of
+    // this goes unused. We could try a #[repr(packed)] and see how that
+    // effects performance
+    data: [u16; LINE_CAPACITY],
 }
 
 impl SmallVec {
-    pub fn as_slice(&self) -> &[u8] {
+    pub fn as_slice(&self) -> &[u16] {
         &self.data[0..self.length]
     }
 
-    pub fn insert(&mut self, v: u8) {
+    pub fn insert(&mut self, v: u16) {
         self.data[self.length] = v;
         self.length += 1;
     }
 
-    pub fn next_smallest(&self, than: u8) -> Option<u8> {
+    /// Remove `v`, keeping the rest in insertion order. False if it wasn't there.
+    pub fn remove(&mut self, v: u16) -> bool {
+        let Some(i) = self.as_slice().iter().position(|d| *d == v) else {
+            return false;
+        };
+        self.data.copy_within(i + 1..self.length, i);
+        self.length -= 1;
+        true
+    }
+
+    /// Keep only the values `f` returns true for, in order
+    pub fn retain(&mut self, mut f: impl FnMut(u16) -> bool) {
+        let mut kept = 0;
+        for i in 0..self.length {
+            if f(self.data[i]) {
+                self.data[kept] = self.data[i];
+                kept += 1;
+            }
+        }
+        self.length = kept;
+    }
+
+    pub fn next_smallest(&self, than: u16) -> Option<u16> {
         self.data[0..self.length]
             .iter()
             .filter(|d| **d < than)
@@ -35,7 +66,7 @@ impl SmallVec {
             .copied()
     }
 
-    pub fn next_biggest(&self, than: u8) -> Option<u8> {
+    pub fn next_biggest(&self, than: u16) -> Option<u16> {
         self.data[0..self.length]
             .iter()
             .filter(|d| **d > than)
@@ -55,6 +86,12 @@ impl SmallVec {
 /// the overheads are bad. This is *significantly* faster.
 pub struct CoordinateLookupTable(Vec<SmallVec>);
 
+impl Default for CoordinateLookupTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CoordinateLookupTable {
     pub fn new() -> Self {
         let mut first_tier = Vec::with_capacity(200);
@@ -74,7 +111,7 @@ impl CoordinateLookupTable {
     pub fn contains(&self, first: u32, second: u32) -> bool {
         self.0
             .get(first as usize)
-            .is_some_and(|set| set.as_slice().contains(&(second as u8)))
+            .is_some_and(|set| set.as_slice().contains(&(second as u16)))
     }
 
     pub fn get(&self, first: u32) -> Option<&SmallVec> {
@@ -87,7 +124,14 @@ impl CoordinateLookupTable {
             // to just ignore this but it would obviously be a deranged API
             return;
         };
-        second_tier.insert(second as u8);
+        second_tier.insert(second as u16);
+    }
+
+    /// False if it wasn't there
+    pub fn remove(&mut self, first: u32, second: u32) -> bool {
+        self.0
+            .get_mut(first as usize)
+            .is_some_and(|second_tier| second_tier.remove(second as u16))
     }
 
     // Special purpose function for when we insert a new temporary block during part 2
@@ -106,6 +150,10 @@ impl CoordinateLookupTable {
     pub fn iter(&self) -> impl Iterator<Item = &SmallVec> {
         self.0.iter()
     }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut SmallVec> {
+        self.0.iter_mut()
+    }
 }
 
 #[derive(Clone)]
@@ -202,7 +250,7 @@ impl NormalizedDirectedLineSegment {
                 } else {
                     let overlap_start = min_y1.max(min_y2);
                     let overlap_end = max_y1.min(max_y2);
-                    overlap_end - overlap_start
+                    overlap_end - overlap_start + 1
                 }
             }
 
@@ -222,7 +270,7 @@ impl NormalizedDirectedLineSegment {
                 } else {
                     let overlap_start = min_x1.max(min_x2);
                     let overlap_end = max_x1.min(max_x2);
-                    overlap_end - overlap_start
+                    overlap_end - overlap_start + 1
                 }
             }
 
@@ -253,11 +301,110 @@ impl NormalizedDirectedLineSegment {
     }
 }
 
+/// Merge parallel segments that share cells. All the segments must have the same
+/// normalized direction.
+fn merge_overlapping(
+    mut segments: Vec<NormalizedDirectedLineSegment>,
+) -> Vec<NormalizedDirectedLineSegment> {
+    // (line the segment is on, where along the line it starts)
+    let key = |segment: &NormalizedDirectedLineSegment| match segment.direction {
+        NormalizedDirection::Horizontal => (segment.origin.y, segment.origin.x),
+        NormalizedDirection::Vertical => (segment.origin.x, segment.origin.y),
+    };
+
+    segments.sort_unstable_by_key(key);
+
+    let mut merged: Vec<NormalizedDirectedLineSegment> = Vec::with_capacity(segments.len());
+
+    for segment in segments {
+        if let Some(last) = merged.last_mut() {
+            let (last_line, last_start) = key(last);
+            let (line, start) = key(&segment);
+
+            if line == last_line && start <= last_start + last.step {
+                last.step = last.step.max(start + segment.step - last_start);
+                continue;
+            }
+        }
+
+        merged.push(segment);
+    }
+
+    merged
+}
+
 pub enum MovePosition {
     InGrid(Point),
     OffGrid(Point),
 }
 
+/// How part 2 decides that a candidate obsticle traps the guard in a loop.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LoopDetection {
+    /// Remember every (turn point, direction) in a hash set and stop on the
+    /// first repeat. Set is cleared, not reallocated, between candidates.
+    HashSet,
+    /// Treat the sequence of turn states as an iterated function and run
+    /// Brent's cycle detection over it. No memory beyond a couple of states.
+    Brent,
+    /// Precompute the fate of every turn state without the candidate and only
+    /// re-walk the parts of the path that run into it. See [`TurnGraph`].
+    TurnGraph,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    NoStart,
+    MultipleStarts,
+    UnexpectedByte {
+        offset: usize,
+        byte: u8,
+    },
+    /// Every row must be as long as the first one and end in a newline
+    RaggedRow {
+        row: u32,
+    },
+    TooBig {
+        width: u32,
+        height: u32,
+    },
+    TooManyObsticlesInRow {
+        row: u32,
+    },
+    TooManyObsticlesInColumn {
+        column: u32,
+    },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty map"),
+            ParseError::NoStart => write!(f, "no guard on the map"),
+            ParseError::MultipleStarts => write!(f, "more than one guard on the map"),
+            ParseError::UnexpectedByte { offset, byte } => {
+                write!(f, "unexpected byte {byte:#04x} at offset {offset}")
+            }
+            ParseError::RaggedRow { row } => write!(f, "row {row} is a different length"),
+            ParseError::TooBig { width, height } => write!(
+                f,
+                "map is {width}x{height}, at most {MAX_SIZE}x{MAX_SIZE} is supported"
+            ),
+            ParseError::TooManyObsticlesInRow { row } => write!(
+                f,
+                "row {row} has more than {MAX_OBSTICLES_PER_LINE} obsticles"
+            ),
+            ParseError::TooManyObsticlesInColumn { column } => write!(
+                f,
+                "column {column} has more than {MAX_OBSTICLES_PER_LINE} obsticles"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
 pub struct SparseGrid {
     pub width: u32,
     pub height: u32,
@@ -266,12 +413,9 @@ pub struct SparseGrid {
     pub y_to_x: CoordinateLookupTable,
 }
 
-impl SparseGrid {
-    pub fn from_bytes(input: &[u8]) -> Self {
-        let newline_target = SimdBlock::splat(b'\n');
-        let start_target = SimdBlock::splat(b'^');
-        let obsticles_target = SimdBlock::splat(b'#');
-
+dispatch::multiversion! {
+    /// `SparseGrid::from_bytes`, built for each SIMD level
+    fn scan_bytes(input: &[u8]) -> SparseGrid {
         let mut start_point: Option<Point> = None;
         let mut width: Option<u32> = None;
         let mut height: u32 = 0;
@@ -282,11 +426,26 @@ impl SparseGrid {
         let mut y_to_x = CoordinateLookupTable::new();
 
         for (chunk_idx, c) in input.chunks(64).enumerate() {
-            let chunk = SimdBlock::load_or_default(c);
+            let chunk = Block::load(c);
+
+            // Find newlines first, the start may be later in the same chunk
+            // and we need the width to place it
+            let mask = chunk.mask(b'\n');
+
+            // We've found our first newline!
+            if mask != 0 && width.is_none() {
+                let idx_within_chunk = mask.trailing_zeros();
+                let idx_within_row = chunk_idx as u32 * 64 + idx_within_chunk;
+                width = Some(idx_within_row);
+                // Every row is the same length, so we know how many there will be
+                y_to_x.resize((input.len() as u32).div_ceil(idx_within_row + 1));
+            }
+
+            height += mask.count_ones();
 
             // Find start, if we haven't found it already
             if start_point.is_none() {
-                let mask = chunk.simd_eq(start_target).to_bitmask();
+                let mask = chunk.mask(b'^');
                 if mask != 0 {
                     let idx = mask.trailing_zeros();
                     let i = chunk_idx as u32 * 64 + idx;
@@ -296,21 +455,8 @@ impl SparseGrid {
                 }
             }
 
-            // Find newlines
-            let mask = chunk.simd_eq(newline_target).to_bitmask();
-
-            // We've found our first newline!
-            if mask != 0 && width.is_none() {
-                let idx_within_chunk = mask.trailing_zeros();
-                let idx_within_row = chunk_idx as u32 * 64 + idx_within_chunk;
-                width = Some(idx_within_row);
-                y_to_x.resize(idx_within_row);
-            }
-
-            height += mask.count_ones();
-
             // Find obsticles
-            let mask = chunk.simd_eq(obsticles_target).to_bitmask();
+            let mask = chunk.mask(b'#');
 
             if mask != 0 {
                 let mut next_mask = mask;
@@ -334,13 +480,13 @@ impl SparseGrid {
         let width = width.unwrap();
         let mut x_to_y = CoordinateLookupTable::with_size(width);
 
-        for (row, y) in y_to_x.iter().zip(0_u8..) {
+        for (row, y) in y_to_x.iter().zip(0_u32..) {
             for x in row.as_slice() {
-                x_to_y.insert(*x as u32, y as u32);
+                x_to_y.insert(*x as u32, y);
             }
         }
 
-        Self {
+        SparseGrid {
             width,
             height,
             start_point: start_point.unwrap(),
@@ -348,12 +494,91 @@ impl SparseGrid {
             y_to_x,
         }
     }
+}
+
+impl SparseGrid {
+    /// Check the map is one we can hold before handing it to `from_bytes`, which
+    /// trusts its input and will panic or give nonsense on anything else.
+    pub fn try_from_bytes(input: &[u8]) -> Result<Self, ParseError> {
+        if input.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        let width = input
+            .iter()
+            .position(|&c| c == b'\n')
+            .unwrap_or(input.len()) as u32;
+        let height = (input.len() as u32).div_ceil(width + 1);
+
+        if width > MAX_SIZE || height > MAX_SIZE {
+            return Err(ParseError::TooBig { width, height });
+        }
+
+        let mut per_column = vec![0; width as usize];
+        let mut found_start = false;
+
+        for (row_bytes, y) in input.chunks(width as usize + 1).zip(0_u32..) {
+            if row_bytes.len() != width as usize + 1 || row_bytes[width as usize] != b'\n' {
+                return Err(ParseError::RaggedRow { row: y });
+            }
+
+            let mut per_row = 0;
+
+            for (&byte, x) in row_bytes[..width as usize].iter().zip(0_u32..) {
+                match byte {
+                    b'.' => {}
+                    b'#' => {
+                        per_row += 1;
+                        per_column[x as usize] += 1;
+
+                        if per_row > MAX_OBSTICLES_PER_LINE {
+                            return Err(ParseError::TooManyObsticlesInRow { row: y });
+                        }
+                        if per_column[x as usize] > MAX_OBSTICLES_PER_LINE {
+                            return Err(ParseError::TooManyObsticlesInColumn { column: x });
+                        }
+                    }
+                    b'^' if found_start => return Err(ParseError::MultipleStarts),
+                    b'^' => found_start = true,
+                    byte => {
+                        return Err(ParseError::UnexpectedByte {
+                            offset: (y * (width + 1) + x) as usize,
+                            byte,
+                        })
+                    }
+                }
+            }
+        }
+
+        if !found_start {
+            return Err(ParseError::NoStart);
+        }
+
+        Ok(Self::from_bytes(input))
+    }
+
+    pub fn from_bytes(input: &[u8]) -> Self {
+        scan_bytes(input)
+    }
+
+    /// Every obsticle on the grid, row by row
+    pub fn obsticles(&self) -> Vec<Point> {
+        let mut obsticles = Vec::new();
+
+        for (row, y) in self.y_to_x.iter().zip(0_u32..) {
+            let mut xs = row.as_slice().to_vec();
+            xs.sort_unstable();
+            obsticles.extend(xs.into_iter().map(|x| Point::new(x as u32, y)));
+        }
+
+        obsticles
+    }
 
     pub fn move_to_next_obsticle_above(&self, p: Point) -> MovePosition {
         let maybe_point = self
             .x_to_y
             .get(p.x)
-            .and_then(|set| set.next_smallest(p.y as u8))
+            .and_then(|set| set.next_smallest(p.y as u16))
             .map(|y| Point::new(p.x, y as u32 + 1));
 
         match maybe_point {
@@ -366,7 +591,7 @@ impl SparseGrid {
         let maybe_point = self
             .x_to_y
             .get(p.x)
-            .and_then(|set| set.next_biggest(p.y as u8))
+            .and_then(|set| set.next_biggest(p.y as u16))
             .map(|y| Point::new(p.x, y as u32 - 1));
 
         match maybe_point {
@@ -379,7 +604,7 @@ impl SparseGrid {
         let maybe_point = self
             .y_to_x
             .get(p.y)
-            .and_then(|set| set.next_smallest(p.x as u8))
+            .and_then(|set| set.next_smallest(p.x as u16))
             .map(|x| Point::new(x as u32 + 1, p.y));
 
         match maybe_point {
@@ -392,7 +617,7 @@ impl SparseGrid {
         let maybe_point = self
             .y_to_x
             .get(p.y)
-            .and_then(|set| set.next_biggest(p.x as u8))
+            .and_then(|set| set.next_biggest(p.x as u16))
             .map(|x| Point::new(x as u32 - 1, p.y));
 
         match maybe_point {
@@ -401,6 +626,54 @@ impl SparseGrid {
         }
     }
 
+    pub fn move_to_next_obsticle(&self, p: Point, direction: Direction) -> MovePosition {
+        match direction {
+            Direction::Up => self.move_to_next_obsticle_above(p),
+            Direction::Down => self.move_to_next_obsticle_below(p, self.height),
+            Direction::Left => self.move_to_next_obsticle_to_left(p),
+            Direction::Right => self.move_to_next_obsticle_to_right(p, self.width),
+        }
+    }
+
+    /// The guard state after the next turn, or `None` if the guard walks off the grid first.
+    pub fn next_turn(&self, (p, direction): (Point, Direction)) -> Option<(Point, Direction)> {
+        match self.move_to_next_obsticle(p, direction) {
+            MovePosition::InGrid(next_position) => {
+                Some((next_position, direction.rotate_clockwise()))
+            }
+            MovePosition::OffGrid(_) => None,
+        }
+    }
+
+    /// Brent's algorithm over the turn states. The hare runs ahead and the tortoise
+    /// teleports to it every power of two steps, so if there is a cycle the hare will
+    /// land on the tortoise once the power exceeds the cycle length. If the hare walks
+    /// off the grid there is no cycle.
+    fn is_loop_brent(&self, start: (Point, Direction)) -> bool {
+        let mut power = 1_u32;
+        let mut length = 1_u32;
+        let mut tortoise = start;
+        let Some(mut hare) = self.next_turn(start) else {
+            return false;
+        };
+
+        while tortoise != hare {
+            if power == length {
+                tortoise = hare;
+                power *= 2;
+                length = 0;
+            }
+
+            let Some(next) = self.next_turn(hare) else {
+                return false;
+            };
+            hare = next;
+            length += 1;
+        }
+
+        true
+    }
+
     pub fn part_1(&self) -> u32 {
         let mut direction = Direction::Up;
 
@@ -437,13 +710,24 @@ impl SparseGrid {
             }
         }
 
+        // Merge segments that overlap along the same row or column. After that every
+        // visited cell is on exactly one horizontal and/or one vertical segment, so
+        // the only cells counted twice are where the two kinds cross.
+        let (horizontal, vertical): (Vec<_>, Vec<_>) = segments
+            .into_iter()
+            .partition(|segment| segment.direction == NormalizedDirection::Horizontal);
+        let horizontal = merge_overlapping(horizontal);
+        let vertical = merge_overlapping(vertical);
+
         let mut total_visited = 0;
 
-        for (i, segment) in segments.iter().enumerate() {
+        for segment in horizontal.iter().chain(vertical.iter()) {
             // Origin plus every step along the way.
             total_visited += 1 + segment.step;
+        }
 
-            for other in segments.iter().skip(i + 1) {
+        for segment in horizontal.iter() {
+            for other in vertical.iter() {
                 total_visited -= segment.step_overlap_count(other);
             }
         }
@@ -451,7 +735,13 @@ impl SparseGrid {
         total_visited
     }
 
-    pub fn part_2(&mut self) -> u32 {
+    pub fn part_2(&mut self, loop_detection: LoopDetection) -> u32 {
+        self.part_2_obsticles(loop_detection).len() as u32
+    }
+
+    /// Every position where a new obsticle would trap the guard in a loop, in the
+    /// order they are found along the guard's route
+    pub fn part_2_obsticles(&mut self, loop_detection: LoopDetection) -> Vec<Point> {
         let mut segments: Vec<DirectedLineSegment> = Vec::with_capacity(1024);
 
         //
@@ -490,7 +780,7 @@ impl SparseGrid {
         // Use list of paths to find potential places for obsticles
         //
 
-        let mut total_potential_obsticles = 0;
+        let mut potential_obsticles = Vec::new();
 
         // Set of all placed candidates. Prevents retesting loops in a place we've
         // already put a block.
@@ -501,18 +791,30 @@ impl SparseGrid {
         // the need for reallocation
         let mut visited = FnvHashSet::<(Point, Direction)>::default();
 
+        // Fate of every turn state before any candidate is placed
+        let turn_graph = match loop_detection {
+            LoopDetection::TurnGraph => Some(TurnGraph::from_grid(self)),
+            _ => None,
+        };
+
         for segment in segments.iter() {
             for step in 0..=segment.step {
                 let start_position = segment.origin.step_n(segment.direction, step);
                 let block_position = start_position.step(segment.direction);
 
+                // The last step walks off the grid, can't put a block out there
+                if block_position.x >= self.width || block_position.y >= self.height {
+                    continue;
+                }
+
                 if !placed.insert(block_position) {
                     continue;
                 }
 
-                // Let's see if a block already exists!
+                // Let's see if a block already exists! The guard is stood on the
+                // start so we can't put one there either
                 if self.x_to_y.contains(block_position.x, block_position.y)
-                // || block_position == self.start_point
+                    || block_position == self.start_point
                 {
                     continue;
                 }
@@ -524,28 +826,33 @@ impl SparseGrid {
                 self.y_to_x.insert(block_position.y, block_position.x);
                 self.x_to_y.insert(block_position.x, block_position.y);
 
-                loop {
-                    let next_position = match direction {
-                        Direction::Up => self.move_to_next_obsticle_above(position),
-                        Direction::Down => self.move_to_next_obsticle_below(position, self.height),
-                        Direction::Left => self.move_to_next_obsticle_to_left(position),
-                        Direction::Right => {
-                            self.move_to_next_obsticle_to_right(position, self.width)
-                        }
-                    };
-
-                    match next_position {
-                        MovePosition::InGrid(next_position) => {
+                match loop_detection {
+                    LoopDetection::HashSet => {
+                        while let MovePosition::InGrid(next_position) =
+                            self.move_to_next_obsticle(position, direction)
+                        {
                             if !visited.insert((next_position, direction)) {
-                                total_potential_obsticles += 1;
+                                potential_obsticles.push(block_position);
                                 break;
                             }
 
                             position = next_position;
                             direction = direction.rotate_clockwise();
                         }
-                        MovePosition::OffGrid(_) => {
-                            break;
+                    }
+                    LoopDetection::Brent => {
+                        if self.is_loop_brent((position, direction)) {
+                            potential_obsticles.push(block_position);
+                        }
+                    }
+                    LoopDetection::TurnGraph => {
+                        let turn_graph = turn_graph.as_ref().expect("built above");
+                        if turn_graph.is_loop_with_obsticle(
+                            self,
+                            block_position,
+                            (position, direction),
+                        ) {
+                            potential_obsticles.push(block_position);
                         }
                     }
                 }
@@ -557,48 +864,270 @@ impl SparseGrid {
             }
         }
 
-        total_potential_obsticles
+        potential_obsticles
     }
 }
 
+#[cfg(test)]
+use crate::answers::expected;
+#[cfg(test)]
 const INPUT: &[u8] = include_bytes!("input.txt");
+#[cfg(test)]
 const EXAMPLE: &[u8] = include_bytes!("example.txt");
 
+/// Obviously correct parse to check the SIMD one against
+#[cfg(test)]
+fn scalar_obsticles(input: &[u8]) -> Vec<Point> {
+    input
+        .split(|c| *c == b'\n')
+        .zip(0_u32..)
+        .flat_map(|(row, y)| {
+            row.iter()
+                .zip(0_u32..)
+                .filter(|(c, _)| **c == b'#')
+                .map(move |(_, x)| Point::new(x, y))
+        })
+        .collect()
+}
+
+#[test]
+fn test_try_from_bytes_round_trips() {
+    use crate::generate::{generate, MapOptions};
+
+    for seed in 0..50 {
+        let size = 1 + seed as u32 * 5;
+        let map = generate(
+            seed,
+            &MapOptions {
+                max_obsticles_per_line: Some(MAX_OBSTICLES_PER_LINE as u32),
+                ..MapOptions::new(size, 600 - size, 0.1)
+            },
+        );
+
+        let grid = SparseGrid::try_from_bytes(&map.bytes).expect("valid map");
+        assert_eq!(grid.width, map.width);
+        assert_eq!(grid.height, map.height);
+        assert_eq!(grid.start_point, map.start_point);
+        assert_eq!(
+            grid.obsticles(),
+            scalar_obsticles(&map.bytes),
+            "seed {seed}"
+        );
+    }
+}
+
+#[test]
+fn test_try_from_bytes_errors() {
+    let parse = |input: &[u8]| SparseGrid::try_from_bytes(input).err();
+
+    assert_eq!(parse(b""), Some(ParseError::Empty));
+    assert_eq!(parse(b"...\n.#.\n"), Some(ParseError::NoStart));
+    assert_eq!(parse(b"^..\n..^\n"), Some(ParseError::MultipleStarts));
+    assert_eq!(
+        parse(b"^..\n.x.\n"),
+        Some(ParseError::UnexpectedByte {
+            offset: 5,
+            byte: b'x'
+        })
+    );
+    assert_eq!(parse(b"^..\n..\n"), Some(ParseError::RaggedRow { row: 1 }));
+    assert_eq!(parse(b"^..\n..."), Some(ParseError::RaggedRow { row: 1 }));
+    assert_eq!(
+        parse(b"^..\r\n...\r\n"),
+        Some(ParseError::UnexpectedByte {
+            offset: 3,
+            byte: b'\r'
+        })
+    );
+
+    let wide = [vec![b'.'; MAX_SIZE as usize + 1], vec![b'\n']].concat();
+    assert_eq!(
+        parse(&wide),
+        Some(ParseError::TooBig {
+            width: MAX_SIZE + 1,
+            height: 1
+        })
+    );
+
+    let crowded = [vec![b'#'; MAX_OBSTICLES_PER_LINE + 1], vec![b'^', b'\n']].concat();
+    assert_eq!(
+        parse(&crowded),
+        Some(ParseError::TooManyObsticlesInRow { row: 0 })
+    );
+}
+
+#[test]
+fn test_part_2_full_line() {
+    // Row 0 as full as a map allows, and the guard walks straight up through it
+    // so part 2 tries a block in that row
+    let map = |obsticles: usize| {
+        let mut rows = vec![[vec![b'#'; obsticles], vec![b'.'; 50 - obsticles]].concat()];
+        rows.extend((0..4).map(|_| vec![b'.'; 50]));
+        rows[4][40] = b'^';
+        for row in rows.iter_mut() {
+            row.push(b'\n');
+        }
+        rows.concat()
+    };
+
+    assert_eq!(
+        SparseGrid::try_from_bytes(&map(MAX_OBSTICLES_PER_LINE + 1)).err(),
+        Some(ParseError::TooManyObsticlesInRow { row: 0 })
+    );
+
+    for loop_detection in [
+        LoopDetection::HashSet,
+        LoopDetection::Brent,
+        LoopDetection::TurnGraph,
+    ] {
+        let mut grid = SparseGrid::try_from_bytes(&map(MAX_OBSTICLES_PER_LINE)).unwrap();
+        assert_eq!(grid.part_2(loop_detection), 0, "{loop_detection:?}");
+    }
+}
+
+#[test]
+fn test_sparse_part_2_example() {
+    for loop_detection in [
+        LoopDetection::HashSet,
+        LoopDetection::Brent,
+        LoopDetection::TurnGraph,
+    ] {
+        let mut grid = SparseGrid::from_bytes(EXAMPLE);
+        assert_eq!(grid.part_2(loop_detection), 6, "{:?}", loop_detection);
+    }
+}
+
+#[test]
+fn test_from_bytes_every_level() {
+    let detected = SparseGrid::from_bytes(INPUT);
+    let expected_start = expected(INPUT, "start");
+
+    for level in crate::dispatch::supported() {
+        let grid = crate::dispatch::with_level(level, || SparseGrid::from_bytes(INPUT));
+
+        assert_eq!(
+            (grid.width, grid.height, grid.start_point),
+            (130, 130, expected_start),
+            "{level:?}"
+        );
+        assert_eq!(grid.obsticles(), detected.obsticles(), "{level:?}");
+        assert_eq!(
+            grid.obsticles().len(),
+            scalar_obsticles(INPUT).len(),
+            "{level:?}"
+        );
+    }
+}
+
+#[cfg(all(test, feature = "bench"))]
+fn bench_sparse_prelude_level(b: &mut test::Bencher, level: crate::dispatch::Level) {
+    // Benches the detected level instead if this CPU can't run `level`
+    let level = level.min(crate::dispatch::detected());
+    crate::dispatch::with_level(level, || {
+        b.iter(|| SparseGrid::from_bytes(test::black_box(INPUT)))
+    });
+}
+
+#[cfg(all(test, feature = "bench"))]
+#[bench]
+fn bench_sparse_prelude_baseline(b: &mut test::Bencher) {
+    bench_sparse_prelude_level(b, crate::dispatch::Level::Baseline);
+}
+
+#[cfg(all(test, feature = "bench"))]
+#[bench]
+fn bench_sparse_prelude_avx2(b: &mut test::Bencher) {
+    bench_sparse_prelude_level(b, crate::dispatch::Level::Avx2);
+}
+
+#[cfg(all(test, feature = "bench"))]
+#[bench]
+fn bench_sparse_prelude_avx512(b: &mut test::Bencher) {
+    bench_sparse_prelude_level(b, crate::dispatch::Level::Avx512);
+}
+
+#[cfg(all(test, feature = "bench"))]
 #[bench]
 fn bench_sparse_prelude(b: &mut test::Bencher) {
+    let expected_start = expected(INPUT, "start");
+
     b.iter(|| {
         let grid = SparseGrid::from_bytes(INPUT);
 
         assert_eq!(grid.width, 130);
         assert_eq!(grid.height, 130);
-        assert_eq!(grid.start_point, Point::new(41, 73));
+        assert_eq!(grid.start_point, expected_start);
     });
 }
 
+#[cfg(all(test, feature = "bench"))]
 #[bench]
 fn bench_sparse_part_1_complete(b: &mut test::Bencher) {
+    let expected_start = expected(INPUT, "start");
+    let expected_count = expected(INPUT, "part_1");
+
     b.iter(|| {
         let grid = SparseGrid::from_bytes(INPUT);
 
         assert_eq!(grid.width, 130);
         assert_eq!(grid.height, 130);
-        assert_eq!(grid.start_point, Point::new(41, 73));
+        assert_eq!(grid.start_point, expected_start);
 
         let count = grid.part_1();
-        assert_eq!(count, 4939);
+        assert_eq!(count, expected_count);
     });
 }
 
+#[cfg(all(test, feature = "bench"))]
 #[bench]
 fn bench_sparse_part_2_complete(b: &mut test::Bencher) {
+    let expected_start = expected(INPUT, "start");
+    let expected_count = expected(INPUT, "part_2");
+
+    b.iter(|| {
+        let mut grid = SparseGrid::from_bytes(INPUT);
+
+        assert_eq!(grid.width, 130);
+        assert_eq!(grid.height, 130);
+        assert_eq!(grid.start_point, expected_start);
+
+        let count = grid.part_2(LoopDetection::HashSet);
+        assert_eq!(count, expected_count);
+    });
+}
+
+#[cfg(all(test, feature = "bench"))]
+#[bench]
+fn bench_sparse_part_2_brent_complete(b: &mut test::Bencher) {
+    let expected_start = expected(INPUT, "start");
+    let expected_count = expected(INPUT, "part_2");
+
+    b.iter(|| {
+        let mut grid = SparseGrid::from_bytes(INPUT);
+
+        assert_eq!(grid.width, 130);
+        assert_eq!(grid.height, 130);
+        assert_eq!(grid.start_point, expected_start);
+
+        let count = grid.part_2(LoopDetection::Brent);
+        assert_eq!(count, expected_count);
+    });
+}
+
+#[cfg(all(test, feature = "bench"))]
+#[bench]
+fn bench_sparse_part_2_turn_graph_complete(b: &mut test::Bencher) {
+    let expected_start = expected(INPUT, "start");
+    let expected_count = expected(INPUT, "part_2");
+
     b.iter(|| {
         let mut grid = SparseGrid::from_bytes(INPUT);
 
         assert_eq!(grid.width, 130);
         assert_eq!(grid.height, 130);
-        assert_eq!(grid.start_point, Point::new(41, 73));
+        assert_eq!(grid.start_point, expected_start);
 
-        let count = grid.part_2();
-        assert_eq!(count, 1434);
+        let count = grid.part_2(LoopDetection::TurnGraph);
+        assert_eq!(count, expected_count);
     });
 }
@@ -0,0 +1,369 @@
+use crate::{
+    direction::Direction,
+    point::Point,
+    sparse::{MovePosition, SparseGrid},
+};
+
+const NONE: u32 = u32::MAX;
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Right,
+    Direction::Down,
+    Direction::Left,
+];
+
+/// What eventually happens to a guard standing in a turn state.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TurnStatus {
+    /// The guard makes `turns` more turns and then walks off the grid.
+    Exits { turns: u32 },
+    /// The guard walks `tail` segments and then goes round cycle number
+    /// `cycle` forever. `cycle_length` is the number of turns in the cycle.
+    Loops {
+        cycle: u32,
+        cycle_length: u32,
+        tail: u32,
+    },
+}
+
+/// A turn state is a guard that has just bumped into an obsticle and rotated.
+/// From there the next turn state is fixed, so the states form a functional
+/// graph: every node has exactly one outgoing edge (or none, if the guard exits).
+///
+/// We label every node once up front so part 2 can mostly walk a flat array
+/// rather than scanning the lookup tables on every step.
+pub struct TurnGraph {
+    width: u32,
+    height: u32,
+    /// (x, y, direction) -> index into `states`, or NONE if it isn't a turn state
+    ids: Vec<u32>,
+    states: Vec<(Point, Direction)>,
+    /// Index of the next turn state or NONE if the guard walks off the grid
+    next: Vec<u32>,
+    statuses: Vec<TurnStatus>,
+}
+
+impl TurnGraph {
+    pub fn from_grid(grid: &SparseGrid) -> Self {
+        let width = grid.width;
+        let height = grid.height;
+
+        let mut ids = vec![NONE; (width * height) as usize * 4];
+        let mut states = Vec::new();
+
+        // A guard walking in `direction` into an obsticle stands on the opposite
+        // side of it and then rotates. Discover every such state.
+        for (row, y) in grid.y_to_x.iter().zip(0_u32..) {
+            for &x in row.as_slice() {
+                let obsticle = Point::new(x as u32, y);
+
+                for direction in DIRECTIONS {
+                    let Some(position) = step_within(obsticle, direction.opposite(), width, height)
+                    else {
+                        continue;
+                    };
+
+                    if grid.x_to_y.contains(position.x, position.y) {
+                        continue;
+                    }
+
+                    let state = (position, direction.rotate_clockwise());
+                    let idx = state_index(state, width);
+                    if ids[idx] == NONE {
+                        ids[idx] = states.len() as u32;
+                        states.push(state);
+                    }
+                }
+            }
+        }
+
+        let next = states
+            .iter()
+            .map(|&state| match grid.next_turn(state) {
+                Some(next_state) => ids[state_index(next_state, width)],
+                None => NONE,
+            })
+            .collect::<Vec<_>>();
+
+        let statuses = label(&next);
+
+        Self {
+            width,
+            height,
+            ids,
+            states,
+            next,
+            statuses,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    fn id(&self, (p, direction): (Point, Direction)) -> Option<u32> {
+        if p.x >= self.width || p.y >= self.height {
+            return None;
+        }
+
+        let id = self.ids[state_index((p, direction), self.width)];
+        (id != NONE).then_some(id)
+    }
+
+    /// The fate of a guard in the given turn state, `None` if it isn't a turn state
+    pub fn status(&self, state: (Point, Direction)) -> Option<TurnStatus> {
+        self.id(state).map(|id| self.statuses[id as usize])
+    }
+
+    /// The turn state that follows the given one, `None` if the guard exits
+    /// or it isn't a turn state
+    pub fn next(&self, state: (Point, Direction)) -> Option<(Point, Direction)> {
+        let next = self.next[self.id(state)? as usize];
+        (next != NONE).then(|| self.states[next as usize])
+    }
+
+    /// Does the guard loop once a new obsticle is placed at `block`? `start` is the
+    /// guard having just turned in front of it.
+    ///
+    /// The obsticle only changes the path where a segment walks through it, so we
+    /// follow the precomputed successors and check each segment against the block.
+    /// Once the rest of the original path has been checked without touching the
+    /// block the label tells us the answer. Every time we do hit it we're in one of
+    /// the four states around the block, so hitting the same side twice is a loop.
+    pub fn is_loop_with_obsticle(
+        &self,
+        grid: &SparseGrid,
+        block: Point,
+        start: (Point, Direction),
+    ) -> bool {
+        let mut block_sides = start.1 as u8;
+        let mut state = start;
+
+        loop {
+            // We've just turned away from the block so this first segment can't touch it
+            let mut id = match grid.move_to_next_obsticle(state.0, state.1) {
+                MovePosition::InGrid(p) => self
+                    .id((p, state.1.rotate_clockwise()))
+                    .expect("turned in front of an obsticle that isn't in the graph"),
+                MovePosition::OffGrid(_) => return false,
+            };
+
+            let status = self.statuses[id as usize];
+            let remaining = match status {
+                TurnStatus::Exits { turns } => turns + 1,
+                TurnStatus::Loops {
+                    cycle_length, tail, ..
+                } => tail + cycle_length,
+            };
+
+            let mut hit = None;
+
+            for _ in 0..remaining {
+                let (p, direction) = self.states[id as usize];
+                let next = self.next[id as usize];
+
+                let length = if next == NONE {
+                    distance_to_edge(p, direction, self.width, self.height)
+                } else {
+                    distance_along(p, self.states[next as usize].0, direction).unwrap_or(0)
+                };
+
+                if let Some(k) = distance_along(p, block, direction).filter(|k| *k <= length) {
+                    hit = Some((p.step_n(direction, k - 1), direction.rotate_clockwise()));
+                    break;
+                }
+
+                id = next;
+            }
+
+            let Some(next_state) = hit else {
+                return matches!(status, TurnStatus::Loops { .. });
+            };
+
+            let side = next_state.1 as u8;
+            if block_sides & side != 0 {
+                return true;
+            }
+
+            block_sides |= side;
+            state = next_state;
+        }
+    }
+}
+
+fn state_index((p, direction): (Point, Direction), width: u32) -> usize {
+    (p.y * width + p.x) as usize * 4 + direction.index()
+}
+
+fn step_within(p: Point, direction: Direction, width: u32, height: u32) -> Option<Point> {
+    match direction {
+        Direction::Up if p.y == 0 => None,
+        Direction::Left if p.x == 0 => None,
+        Direction::Down if p.y + 1 >= height => None,
+        Direction::Right if p.x + 1 >= width => None,
+        _ => Some(p.step(direction)),
+    }
+}
+
+fn distance_to_edge(p: Point, direction: Direction, width: u32, height: u32) -> u32 {
+    match direction {
+        Direction::Up => p.y,
+        Direction::Down => height - 1 - p.y,
+        Direction::Left => p.x,
+        Direction::Right => width - 1 - p.x,
+    }
+}
+
+/// How many steps in `direction` it takes to get from `from` to `to`, if it's
+/// directly ahead
+fn distance_along(from: Point, to: Point, direction: Direction) -> Option<u32> {
+    match direction {
+        Direction::Up if from.x == to.x && to.y < from.y => Some(from.y - to.y),
+        Direction::Down if from.x == to.x && to.y > from.y => Some(to.y - from.y),
+        Direction::Left if from.y == to.y && to.x < from.x => Some(from.x - to.x),
+        Direction::Right if from.y == to.y && to.x > from.x => Some(to.x - from.x),
+        _ => None,
+    }
+}
+
+/// Label every node of the functional graph in a single pass. Each node is
+/// pushed onto the path at most once, and resolved when the walk reaches an
+/// exit, an already labelled node or a node on the current path (a new cycle).
+fn label(next: &[u32]) -> Vec<TurnStatus> {
+    let mut statuses: Vec<Option<TurnStatus>> = vec![None; next.len()];
+    // Position of a node on the current path, NONE if it isn't on it
+    let mut on_path = vec![NONE; next.len()];
+    let mut path: Vec<u32> = Vec::new();
+    let mut cycles = 0;
+
+    for first in 0..next.len() as u32 {
+        if statuses[first as usize].is_some() {
+            continue;
+        }
+
+        let mut current = first;
+
+        let mut resolved = loop {
+            if current == NONE {
+                break None;
+            }
+
+            if let Some(status) = statuses[current as usize] {
+                break Some(status);
+            }
+
+            let at = on_path[current as usize];
+            if at != NONE {
+                // Found a new cycle, everything on the path from `at` onwards is on it
+                let cycle_length = path.len() as u32 - at;
+                let status = TurnStatus::Loops {
+                    cycle: cycles,
+                    cycle_length,
+                    tail: 0,
+                };
+                cycles += 1;
+
+                for &node in &path[at as usize..] {
+                    statuses[node as usize] = Some(status);
+                    on_path[node as usize] = NONE;
+                }
+                path.truncate(at as usize);
+
+                break Some(status);
+            }
+
+            on_path[current as usize] = path.len() as u32;
+            path.push(current);
+            current = next[current as usize];
+        };
+
+        // Unwind the rest of the path, each node is one step further from its fate
+        while let Some(node) = path.pop() {
+            let status = match resolved {
+                None => TurnStatus::Exits { turns: 0 },
+                Some(TurnStatus::Exits { turns }) => TurnStatus::Exits { turns: turns + 1 },
+                Some(TurnStatus::Loops {
+                    cycle,
+                    cycle_length,
+                    tail,
+                }) => TurnStatus::Loops {
+                    cycle,
+                    cycle_length,
+                    tail: tail + 1,
+                },
+            };
+
+            statuses[node as usize] = Some(status);
+            on_path[node as usize] = NONE;
+            resolved = Some(status);
+        }
+    }
+
+    statuses.into_iter().map(Option::unwrap).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &[u8] = include_bytes!("example.txt");
+
+    #[test]
+    fn test_label() {
+        // 0 -> 1 -> 2 -> exit, 3 -> 4 -> 5 -> 3, 6 -> 4
+        let next = [1, 2, NONE, 4, 5, 3, 4];
+        let statuses = label(&next);
+
+        assert_eq!(statuses[0], TurnStatus::Exits { turns: 2 });
+        assert_eq!(statuses[2], TurnStatus::Exits { turns: 0 });
+        for status in &statuses[3..=5] {
+            assert_eq!(
+                *status,
+                TurnStatus::Loops {
+                    cycle: 0,
+                    cycle_length: 3,
+                    tail: 0
+                }
+            );
+        }
+        assert_eq!(
+            statuses[6],
+            TurnStatus::Loops {
+                cycle: 0,
+                cycle_length: 3,
+                tail: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_example_graph() {
+        let grid = SparseGrid::from_bytes(EXAMPLE);
+        let graph = TurnGraph::from_grid(&grid);
+
+        // The guard walks up from the start and turns under the obsticle at (4, 0)
+        let first_turn = (Point::new(4, 1), Direction::Right);
+        assert_eq!(
+            grid.next_turn((grid.start_point, Direction::Up)),
+            Some(first_turn)
+        );
+        assert_eq!(
+            graph.next(first_turn),
+            Some((Point::new(8, 1), Direction::Down))
+        );
+
+        // Part 1 leaves the grid after 9 more turns, the last at (7, 7)
+        assert_eq!(
+            graph.status(first_turn),
+            Some(TurnStatus::Exits { turns: 9 })
+        );
+
+        // Not a turn state
+        assert_eq!(graph.status((Point::new(0, 0), Direction::Up)), None);
+    }
+}
@@ -0,0 +1,154 @@
+//! Known answers keyed by a hash of the input, so the tests and the bins check
+//! whichever puzzle input is checked out rather than one hard-coded set.
+//!
+//! Each crate keeps an `answers.txt` next to its `Cargo.toml`, with one `<hash>
+//! <part> <answer>` line per answer and the hash as 16 hex digits, and loads it
+//! in its own `answers` module. The `report`, `solve` and `load_report` bins
+//! print the hash of an input they don't know.
+
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+/// 64 bit FNV-1a of the whole input
+pub fn hash(input: &[u8]) -> u64 {
+    input.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AnswersError {
+    /// Not three fields
+    BadLine {
+        line: usize,
+    },
+    BadHash {
+        line: usize,
+    },
+}
+
+impl std::fmt::Display for AnswersError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnswersError::BadLine { line } => {
+                write!(f, "line {line}: expected `<hash> <part> <answer>`")
+            }
+            AnswersError::BadHash { line } => write!(f, "line {line}: the hash isn't hex"),
+        }
+    }
+}
+
+impl std::error::Error for AnswersError {}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Check {
+    Correct,
+    Wrong {
+        expected: String,
+    },
+    /// Nothing's known for this input and part
+    Unknown,
+}
+
+pub struct Answers(HashMap<(u64, String), String>);
+
+impl Answers {
+    /// Blank lines and lines starting with `#` are skipped
+    pub fn parse(text: &str) -> Result<Self, AnswersError> {
+        let mut answers = HashMap::new();
+
+        for (line, content) in (1..).zip(text.lines()) {
+            let content = content.trim();
+            if content.is_empty() || content.starts_with('#') {
+                continue;
+            }
+
+            let fields = content.split_whitespace().collect::<Vec<_>>();
+            let [hash, part, answer] = fields[..] else {
+                return Err(AnswersError::BadLine { line });
+            };
+            let hash = u64::from_str_radix(hash, 16).map_err(|_| AnswersError::BadHash { line })?;
+
+            answers.insert((hash, part.to_string()), answer.to_string());
+        }
+
+        Ok(Self(answers))
+    }
+
+    pub fn get(&self, input: &[u8], part: &str) -> Option<&str> {
+        self.0
+            .get(&(hash(input), part.to_string()))
+            .map(String::as_str)
+    }
+
+    /// The answer parsed as a `T`, `None` if it's unknown or doesn't parse
+    pub fn expected<T: FromStr>(&self, input: &[u8], part: &str) -> Option<T> {
+        self.get(input, part)?.parse().ok()
+    }
+
+    /// Like `expected`, for asserting against. Panics if it's unknown or
+    /// doesn't parse.
+    pub fn require<T: FromStr>(&self, input: &[u8], part: &str) -> T {
+        self.expected(input, part).unwrap_or_else(|| {
+            panic!(
+                "no {part} answer for input {:016x} in answers.txt",
+                hash(input)
+            )
+        })
+    }
+
+    pub fn check(&self, input: &[u8], part: &str, answer: impl Display) -> Check {
+        match self.get(input, part) {
+            None => Check::Unknown,
+            Some(expected) if expected == answer.to_string() => Check::Correct,
+            Some(expected) => Check::Wrong {
+                expected: expected.to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash() {
+        // Published FNV-1a test vectors
+        assert_eq!(hash(b""), 0xcbf29ce484222325);
+        assert_eq!(hash(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(hash(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse("# comment\n\n0000000000000000 part_1 11\n").unwrap();
+        assert_eq!(answers.get(b"", "part_1"), None);
+
+        let text = format!(
+            "{:016x} part_1 11\n{:016x} part_2 31",
+            hash(b"x"),
+            hash(b"x")
+        );
+        let answers = Answers::parse(&text).unwrap();
+        assert_eq!(answers.get(b"x", "part_1"), Some("11"));
+        assert_eq!(answers.expected::<i64>(b"x", "part_2"), Some(31));
+        assert_eq!(answers.require::<i64>(b"x", "part_1"), 11);
+        assert_eq!(answers.check(b"x", "part_1", 11), Check::Correct);
+        assert_eq!(
+            answers.check(b"x", "part_2", 30),
+            Check::Wrong {
+                expected: "31".to_string()
+            }
+        );
+        assert_eq!(answers.check(b"y", "part_1", 11), Check::Unknown);
+
+        assert_eq!(
+            Answers::parse("abc part_1").err(),
+            Some(AnswersError::BadLine { line: 1 })
+        );
+        assert_eq!(
+            Answers::parse("\nxyz part_1 11").err(),
+            Some(AnswersError::BadHash { line: 2 })
+        );
+    }
+}
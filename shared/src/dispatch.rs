@@ -0,0 +1,163 @@
+//! Runtime CPU feature dispatch for the SIMD hot loops, day1's parsers and
+//! day6's `Block` scans. Both crates re-export this as their `dispatch`
+//! module.
+//!
+//! Everything is compiled for the baseline target, which on x86_64 only means
+//! SSE2, so 64 lane blocks and 8 lane vectors get split into 16 byte ones.
+//! `multiversion!` compiles a function body again with AVX2 and AVX-512
+//! enabled and picks one at runtime. Without the `simd` feature that's the
+//! scalar fallback instead.
+//!
+//! Forcing a level only affects the thread that forced it. Threads spawned
+//! while it's forced start out on the detected level, so anything fanning out
+//! to worker threads has to call `with_level` again in each of them.
+
+use std::{cell::Cell, sync::OnceLock};
+
+/// The instruction sets there's a separate build of each hot loop for
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Whatever the target baseline is, SSE2 on x86_64
+    Baseline,
+    Avx2,
+    /// AVX-512F and BW, for byte compares and shuffles on the full 64 lanes
+    Avx512,
+}
+
+impl Level {
+    pub const ALL: [Level; 3] = [Level::Baseline, Level::Avx2, Level::Avx512];
+}
+
+thread_local! {
+    static FORCED: Cell<Option<Level>> = const { Cell::new(None) };
+}
+
+/// The best level this CPU supports, worked out once
+pub fn detected() -> Level {
+    static DETECTED: OnceLock<Level> = OnceLock::new();
+
+    *DETECTED.get_or_init(|| {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx512f") && is_x86_feature_detected!("avx512bw") {
+                return Level::Avx512;
+            }
+            if is_x86_feature_detected!("avx2") {
+                return Level::Avx2;
+            }
+        }
+        Level::Baseline
+    })
+}
+
+/// Every level that can run here, lowest first
+pub fn supported() -> impl Iterator<Item = Level> {
+    Level::ALL.into_iter().filter(|level| *level <= detected())
+}
+
+/// The level dispatched functions use on this thread
+pub fn active() -> Level {
+    FORCED.get().unwrap_or_else(detected)
+}
+
+/// Use `level` on this thread instead of the detected one, or go back to
+/// detecting with `None`. Panics if the CPU doesn't support `level`.
+pub fn force(level: Option<Level>) {
+    if let Some(level) = level {
+        assert!(
+            level <= detected(),
+            "{level:?} isn't supported, the best here is {:?}",
+            detected()
+        );
+    }
+    FORCED.set(level);
+}
+
+/// Run `f` with `level` forced on this thread, then go back to whatever was
+/// forced before, even if `f` panics
+pub fn with_level<T>(level: Level, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<Level>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            FORCED.set(self.0);
+        }
+    }
+
+    let _restore = Restore(FORCED.get());
+    force(Some(level));
+    f()
+}
+
+/// Define a function whose body is compiled once per `Level`, picking the
+/// build for `active()` on every call. Generic parameters take one bound each.
+#[macro_export]
+macro_rules! multiversion {
+    (
+        $(#[$meta:meta])*
+        $vis:vis fn $name:ident $(<$($g:ident: $bound:path),*>)? ($($arg:ident: $ty:ty),*) -> $ret:ty
+        $body:block
+    ) => {
+        $(#[$meta])*
+        $vis fn $name $(<$($g: $bound),*>)? ($($arg: $ty),*) -> $ret {
+            #[inline(always)]
+            fn body $(<$($g: $bound),*>)? ($($arg: $ty),*) -> $ret $body
+
+            #[cfg(target_arch = "x86_64")]
+            #[target_feature(enable = "avx2")]
+            unsafe fn avx2 $(<$($g: $bound),*>)? ($($arg: $ty),*) -> $ret {
+                body $(::<$($g),*>)? ($($arg),*)
+            }
+
+            #[cfg(target_arch = "x86_64")]
+            #[target_feature(enable = "avx512f,avx512bw")]
+            unsafe fn avx512 $(<$($g: $bound),*>)? ($($arg: $ty),*) -> $ret {
+                body $(::<$($g),*>)? ($($arg),*)
+            }
+
+            match $crate::dispatch::active() {
+                // Safety: `active` only returns levels the CPU supports
+                #[cfg(target_arch = "x86_64")]
+                $crate::dispatch::Level::Avx512 => unsafe { avx512 $(::<$($g),*>)? ($($arg),*) },
+                #[cfg(target_arch = "x86_64")]
+                $crate::dispatch::Level::Avx2 => unsafe { avx2 $(::<$($g),*>)? ($($arg),*) },
+                _ => body $(::<$($g),*>)? ($($arg),*),
+            }
+        }
+    };
+}
+
+pub use crate::multiversion;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_force() {
+        assert_eq!(supported().next(), Some(Level::Baseline));
+
+        for level in supported() {
+            assert_eq!(with_level(level, active), level);
+        }
+        assert_eq!(active(), detected());
+    }
+
+    #[test]
+    fn test_with_level_restores_after_panic() {
+        let result = std::panic::catch_unwind(|| {
+            with_level(Level::Baseline, || panic!("inside with_level"));
+        });
+
+        assert!(result.is_err());
+        assert_eq!(FORCED.get(), None);
+    }
+
+    #[test]
+    fn test_force_unsupported() {
+        if detected() < Level::Avx512 {
+            let result = std::panic::catch_unwind(|| force(Some(Level::Avx512)));
+            assert!(result.is_err());
+        }
+    }
+}
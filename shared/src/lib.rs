@@ -0,0 +1,5 @@
+//! Code day1 and day6 both use.
+
+pub mod answers;
+pub mod dispatch;
+pub mod report;
@@ -0,0 +1,168 @@
+//! Timing runs and tabulating them, for the crates' report bins, in place of
+//! pasting `cargo bench` output into comments.
+
+use std::{
+    hint::black_box,
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+/// Timings of one row over a number of runs
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    pub name: &'static str,
+    pub runs: usize,
+    pub median: Duration,
+    pub min: Duration,
+    /// Population standard deviation
+    pub stddev: Duration,
+    /// Input size, for the throughput
+    pub bytes: usize,
+}
+
+impl Stats {
+    pub fn from_durations(name: &'static str, bytes: usize, durations: &[Duration]) -> Self {
+        assert!(!durations.is_empty(), "need at least one run");
+
+        let mut sorted = durations.to_vec();
+        sorted.sort_unstable();
+
+        let middle = sorted.len() / 2;
+        let median = if sorted.len() % 2 == 1 {
+            sorted[middle]
+        } else {
+            (sorted[middle - 1] + sorted[middle]) / 2
+        };
+
+        let mean = sorted.iter().map(Duration::as_secs_f64).sum::<f64>() / sorted.len() as f64;
+        let variance = sorted
+            .iter()
+            .map(|d| (d.as_secs_f64() - mean).powi(2))
+            .sum::<f64>()
+            / sorted.len() as f64;
+
+        Self {
+            name,
+            runs: sorted.len(),
+            median,
+            min: sorted[0],
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+            bytes,
+        }
+    }
+
+    /// Megabytes of input per second at the median
+    pub fn throughput(&self) -> f64 {
+        self.bytes as f64 / self.median.as_secs_f64() / 1e6
+    }
+}
+
+/// Time `runs` calls of `f`, after one untimed warm up call. `bytes` is how
+/// much input each call gets through, for the throughput.
+pub fn time<T>(name: &'static str, bytes: usize, runs: usize, mut f: impl FnMut() -> T) -> Stats {
+    black_box(f());
+
+    let durations = (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .collect::<Vec<_>>();
+
+    Stats::from_durations(name, bytes, &durations)
+}
+
+pub struct Report<T> {
+    pub rows: Vec<Stats>,
+    /// What each row returned, in the same order as `rows`
+    pub answers: Vec<T>,
+    /// Index into `rows` that the relative speeds are against
+    pub baseline: usize,
+}
+
+impl<T> Report<T> {
+    /// How many times faster than the baseline `row` is, by median
+    pub fn relative_speed(&self, row: &Stats) -> f64 {
+        self.rows[self.baseline].median.as_secs_f64() / row.median.as_secs_f64()
+    }
+
+    pub fn write_markdown(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "| variant | median | min | stddev | MB/s | vs {} |",
+            self.rows[self.baseline].name
+        )?;
+        writeln!(writer, "|---|---:|---:|---:|---:|---:|")?;
+        for row in &self.rows {
+            writeln!(
+                writer,
+                "| {} | {:.2?} | {:.2?} | {:.2?} | {:.1} | {:.2}x |",
+                row.name,
+                row.median,
+                row.min,
+                row.stddev,
+                row.throughput(),
+                self.relative_speed(row)
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Times in nanoseconds, one line per row with a header
+    pub fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "variant,runs,median_ns,min_ns,stddev_ns,mb_per_s,relative_speed"
+        )?;
+        for row in &self.rows {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{:.1},{:.3}",
+                row.name,
+                row.runs,
+                row.median.as_nanos(),
+                row.min.as_nanos(),
+                row.stddev.as_nanos(),
+                row.throughput(),
+                self.relative_speed(row)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(durations: &[u64]) -> Vec<Duration> {
+        durations
+            .iter()
+            .copied()
+            .map(Duration::from_millis)
+            .collect()
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::from_durations("odd", 1_000_000, &ms(&[4, 2, 6]));
+        assert_eq!(stats.median, Duration::from_millis(4));
+        assert_eq!(stats.min, Duration::from_millis(2));
+        assert_eq!(stats.runs, 3);
+        assert!((stats.throughput() - 250.0).abs() < 1e-9);
+
+        let stats = Stats::from_durations("even", 0, &ms(&[2, 4, 4, 4, 5, 5, 7, 9]));
+        assert_eq!(stats.median, Duration::from_micros(4500));
+        assert!((stats.stddev.as_secs_f64() - 0.002).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_time() {
+        let mut calls = 0;
+        let stats = time("count", 10, 5, || calls += 1);
+        assert_eq!(calls, 6);
+        assert_eq!((stats.name, stats.runs, stats.bytes), ("count", 5, 10));
+        assert!(stats.min <= stats.median);
+    }
+}